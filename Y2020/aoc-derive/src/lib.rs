@@ -0,0 +1,257 @@
+// '#[derive(AocParse)]' (see 'advent_of_code_rust_2020::parse')
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, Lit, Meta, NestedMeta};
+
+#[derive(Debug, PartialEq)]
+enum Piece {
+    Literal(String),
+    Field(String),
+}
+
+#[proc_macro_derive(AocParse, attributes(aoc))]
+pub fn derive_aoc_parse(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(error(name.span(), "AocParse needs named fields")),
+        },
+        _ => {
+            return Err(error(
+                name.span(),
+                "AocParse can only be derived for structs",
+            ))
+        }
+    };
+    let (format, format_span) = format_attribute(input)?;
+    let pieces = parse_format(&format).map_err(|e| error(format_span, &e))?;
+
+    // Placeholders and fields match one to one
+    let placeholders: Vec<&String> = pieces
+        .iter()
+        .filter_map(|piece| match piece {
+            Piece::Field(field) => Some(field),
+            Piece::Literal(_) => None,
+        })
+        .collect();
+    let idents: Vec<String> = fields
+        .iter()
+        .map(|f| f.ident.as_ref().unwrap().to_string())
+        .collect();
+    if let Some(unknown) = placeholders.iter().find(|&&p| !idents.contains(p)) {
+        let message = format!("'{{{}}}' is not a field of {}", unknown, name);
+        return Err(error(format_span, &message));
+    }
+    for ident in idents.iter() {
+        let message = match placeholders.iter().filter(|&&p| p == ident).count() {
+            1 => continue,
+            0 => format!("'{{{}}}' is missing from the format", ident),
+            n => format!("'{{{}}}' appears {} times in the format", ident, n),
+        };
+        return Err(error(format_span, &message));
+    }
+
+    let steps = pieces.iter().enumerate().map(|(i, piece)| match piece {
+        Piece::Literal(text) => quote! {
+            let (input, _) = __aoc::literal(#text)(input)?;
+        },
+        Piece::Field(field) => {
+            let ident = syn::Ident::new(field, Span::call_site());
+            let ty = &fields
+                .iter()
+                .find(|f| f.ident.as_ref().unwrap() == field)
+                .expect("Should be checked")
+                .ty;
+            let terminator = match pieces.get(i + 1) {
+                Some(Piece::Literal(text)) => quote! { Some(#text) },
+                _ => quote! { None },
+            };
+            quote! {
+                let (input, #ident) = __aoc::field(#field, #terminator, |segment| {
+                    (&&__aoc::Field::<#ty>::new()).parse_segment(segment)
+                })(input)?;
+            }
+        }
+    });
+    let field_names = fields.iter().map(|f| f.ident.as_ref().unwrap());
+
+    Ok(quote! {
+        impl #impl_generics ::advent_of_code_rust_2020::parse::AocParse
+            for #name #ty_generics #where_clause
+        {
+            fn parse(input: &str) -> ::advent_of_code_rust_2020::nom::ParseResult<'_, Self> {
+                use ::advent_of_code_rust_2020::parse as __aoc;
+                #[allow(unused_imports)]
+                use __aoc::{ViaAocParse as _, ViaFromStr as _};
+                #(#steps)*
+                Ok((input, Self { #(#field_names),* }))
+            }
+        }
+
+        impl #impl_generics ::std::str::FromStr for #name #ty_generics #where_clause {
+            type Err = ::advent_of_code_rust_2020::error::RecordError;
+            fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+                ::advent_of_code_rust_2020::parse::from_str(s)
+            }
+        }
+    })
+}
+
+// Value of '#[aoc(format = "...")]'
+fn format_attribute(input: &DeriveInput) -> syn::Result<(String, Span)> {
+    let attr = input
+        .attrs
+        .iter()
+        .find(|a| a.path.is_ident("aoc"))
+        .ok_or_else(|| {
+            error(
+                input.ident.span(),
+                "AocParse needs #[aoc(format = \"...\")]",
+            )
+        })?;
+    if let Meta::List(list) = attr.parse_meta()? {
+        for nested in list.nested.iter() {
+            if let NestedMeta::Meta(Meta::NameValue(nv)) = nested {
+                if let (true, Lit::Str(s)) = (nv.path.is_ident("format"), &nv.lit) {
+                    return Ok((s.value(), s.span()));
+                }
+            }
+        }
+    }
+    Err(error(
+        attr.bracket_token.span,
+        "expected #[aoc(format = \"...\")]",
+    ))
+}
+
+// "{a}-{b} x{{" -> [Field(a), Literal("-"), Field(b), Literal(" x{")]
+fn parse_format(format: &str) -> Result<Vec<Piece>, String> {
+    let mut pieces = Vec::new();
+    let mut literal = String::new();
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                literal.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                literal.push('}');
+            }
+            '{' => {
+                let mut field = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => field.push(c),
+                        None => return Err(format!("unclosed placeholder '{{{}'", field)),
+                    }
+                }
+                if field.is_empty() || !field.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                    return Err(format!("invalid placeholder '{{{}}}'", field));
+                }
+                if !literal.is_empty() {
+                    pieces.push(Piece::Literal(std::mem::take(&mut literal)));
+                } else if let Some(Piece::Field(prev)) = pieces.last() {
+                    return Err(format!(
+                        "'{{{}}}' and '{{{}}}' need literal text between them",
+                        prev, field
+                    ));
+                }
+                pieces.push(Piece::Field(field));
+            }
+            '}' => {
+                return Err(String::from(
+                    "unmatched '}' (write '}}' for a literal brace)",
+                ))
+            }
+            c => literal.push(c),
+        }
+    }
+    if !literal.is_empty() {
+        pieces.push(Piece::Literal(literal));
+    }
+    Ok(pieces)
+}
+
+fn error(span: Span, message: &str) -> syn::Error {
+    syn::Error::new(span, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(name: &str) -> Piece {
+        Piece::Field(name.to_string())
+    }
+
+    fn literal(text: &str) -> Piece {
+        Piece::Literal(text.to_string())
+    }
+
+    #[test]
+    fn splits_fields_and_literals() {
+        assert_eq!(
+            parse_format("{num1}-{num2} {letter}"),
+            Ok(vec![
+                field("num1"),
+                literal("-"),
+                field("num2"),
+                literal(" "),
+                field("letter")
+            ])
+        );
+        assert_eq!(
+            parse_format("x = {x_1}."),
+            Ok(vec![literal("x = "), field("x_1"), literal(".")])
+        );
+        assert_eq!(parse_format("plain"), Ok(vec![literal("plain")]));
+        assert_eq!(parse_format(""), Ok(vec![]));
+    }
+
+    #[test]
+    fn escapes_braces() {
+        assert_eq!(
+            parse_format("{{{a}}} x{{"),
+            Ok(vec![literal("{"), field("a"), literal("} x{")])
+        );
+        assert_eq!(parse_format("{{}}"), Ok(vec![literal("{}")]));
+    }
+
+    #[test]
+    fn rejects_bad_placeholders() {
+        assert_eq!(
+            parse_format("{a"),
+            Err(String::from("unclosed placeholder '{a'"))
+        );
+        assert_eq!(
+            parse_format("{}"),
+            Err(String::from("invalid placeholder '{}'"))
+        );
+        assert_eq!(
+            parse_format("{a b}"),
+            Err(String::from("invalid placeholder '{a b}'"))
+        );
+        assert_eq!(
+            parse_format("{a}{b}"),
+            Err(String::from(
+                "'{a}' and '{b}' need literal text between them"
+            ))
+        );
+        assert!(parse_format("a}").is_err());
+    }
+}
@@ -0,0 +1,47 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+// Generates '$OUT_DIR/embedded_inputs.rs', which lists every 'inputs/*.txt' and
+// 'inputs/*.txt.enc' when the 'embed-inputs' feature is enabled, and nothing otherwise
+fn main() {
+    let out_path = Path::new(&env::var("OUT_DIR").unwrap()).join("embedded_inputs.rs");
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let input_dir = Path::new(&manifest_dir).join("inputs");
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed={}", input_dir.display());
+
+    let mut names: Vec<String> = Vec::new();
+    if env::var_os("CARGO_FEATURE_EMBED_INPUTS").is_some() {
+        names = fs::read_dir(&input_dir)
+            .expect("inputs/ should exist")
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .collect();
+        names.sort();
+    }
+    // ("inputs/dayXX.txt", include_xxx!(".../inputs/dayXX.txt<suffix>")) for each file
+    let entries = |suffix: &str, include: &str| -> String {
+        names
+            .iter()
+            .filter(|name| name.ends_with(suffix))
+            .map(|name| {
+                let path = input_dir.join(name);
+                println!("cargo:rerun-if-changed={}", path.display());
+                let input_name = &name[..name.len() - suffix.len() + ".txt".len()];
+                format!(
+                    "    (\"inputs/{}\", {}!({:?})),\n",
+                    input_name,
+                    include,
+                    path.display().to_string()
+                )
+            })
+            .collect()
+    };
+    let source = format!(
+        "pub static INPUTS: &[(&str, &str)] = &[\n{}];\n\
+         pub static ENCRYPTED_INPUTS: &[(&str, &[u8])] = &[\n{}];\n",
+        entries(".txt", "include_str"),
+        entries(".txt.enc", "include_bytes")
+    );
+    fs::write(out_path, source).expect("Cannot write embedded inputs");
+}
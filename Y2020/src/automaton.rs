@@ -0,0 +1,164 @@
+use std::iter;
+
+use crate::grid::{Grid, Pos};
+
+// Transition rule of a cellular automaton over 'Grid<T>'
+pub trait Rule<T> {
+    // State of the cell at 'pos' in the next generation
+    fn next(&self, grid: &Grid<T>, pos: Pos) -> T;
+    // Cells whose next state may depend on the cell at 'pos'
+    fn dependents(&self, grid: &Grid<T>, pos: Pos) -> Vec<Pos>;
+}
+
+// Steps a grid generation by generation, re-evaluating only the cells
+// which can be affected by the changes of the previous generation
+pub struct Automaton<T, R> {
+    grid: Grid<T>,
+    rule: R,
+    pending: Vec<Pos>,
+    marked: Vec<Vec<bool>>, // Scratch for 'affected_by', all false between steps
+    generation: usize,
+}
+
+impl<T, R> Automaton<T, R>
+where
+    T: PartialEq,
+    R: Rule<T>,
+{
+    pub fn new(grid: Grid<T>, rule: R) -> Self {
+        // Every cell is a candidate in the first generation
+        let pending = grid.positions().collect();
+        let (row_len, col_len) = grid.size();
+        Automaton {
+            grid,
+            rule,
+            pending,
+            marked: vec![vec![false; col_len]; row_len],
+            generation: 0,
+        }
+    }
+
+    pub fn grid(&self) -> &Grid<T> {
+        &self.grid
+    }
+
+    pub fn into_grid(self) -> Grid<T> {
+        self.grid
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    // No cell can change anymore
+    pub fn is_stable(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    // Advances one generation, and returns the cells changed in it
+    pub fn step(&mut self) -> Vec<Pos> {
+        let grid = &self.grid;
+        let rule = &self.rule;
+        let changes: Vec<(Pos, T)> = self
+            .pending
+            .iter()
+            .filter_map(|&pos| {
+                let next = rule.next(grid, pos);
+                if next != grid[pos] {
+                    Some((pos, next))
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        let changed: Vec<Pos> = changes.iter().map(|&(pos, _)| pos).collect();
+        changes
+            .into_iter()
+            .for_each(|(pos, next)| self.grid[pos] = next);
+        self.finish_generation(changed)
+    }
+
+    // Same as 'step', but re-evaluates every cell
+    pub fn step_full(&mut self) -> Vec<Pos> {
+        let (row_len, _) = self.grid.size();
+        let next_rows = (0..row_len).map(|i| self.next_row(i)).collect();
+        self.replace_rows(next_rows)
+    }
+
+    // Same as 'step_full', but computes rows on the rayon thread pool
+    #[cfg(feature = "parallel")]
+    pub fn step_parallel(&mut self) -> Vec<Pos>
+    where
+        T: Send + Sync,
+        R: Sync,
+    {
+        use rayon::prelude::*;
+        let (row_len, _) = self.grid.size();
+        let next_rows = (0..row_len)
+            .into_par_iter()
+            .map(|i| self.next_row(i))
+            .collect();
+        self.replace_rows(next_rows)
+    }
+
+    // Steps until no cell changes, calling 'on_generation' with
+    // the initial grid and the grid after every changing generation
+    pub fn run_until_stable(&mut self, on_generation: impl FnMut(&Grid<T>, &[Pos])) {
+        self.run_until_stable_by(Self::step, on_generation)
+    }
+
+    // Same as 'run_until_stable', advancing generations with 'step'
+    // (one of 'step', 'step_full' or 'step_parallel')
+    pub fn run_until_stable_by(
+        &mut self,
+        step: fn(&mut Self) -> Vec<Pos>,
+        mut on_generation: impl FnMut(&Grid<T>, &[Pos]),
+    ) {
+        on_generation(&self.grid, &[]);
+        loop {
+            let changed = step(self);
+            if changed.is_empty() {
+                break;
+            }
+            on_generation(&self.grid, &changed);
+        }
+    }
+
+    fn next_row(&self, i: usize) -> Vec<T> {
+        let (_, col_len) = self.grid.size();
+        (0..col_len)
+            .map(|j| self.rule.next(&self.grid, (i, j)))
+            .collect()
+    }
+
+    fn replace_rows(&mut self, next_rows: Vec<Vec<T>>) -> Vec<Pos> {
+        let next_grid = Grid::from(next_rows);
+        let changed: Vec<Pos> = self
+            .grid
+            .positions()
+            .filter(|&pos| next_grid[pos] != self.grid[pos])
+            .collect();
+        self.grid = next_grid;
+        self.finish_generation(changed)
+    }
+
+    fn finish_generation(&mut self, changed: Vec<Pos>) -> Vec<Pos> {
+        self.pending = self.affected_by(&changed);
+        self.generation += 1;
+        changed
+    }
+
+    // Changed cells and their dependents, without duplicates
+    fn affected_by(&mut self, changed: &[Pos]) -> Vec<Pos> {
+        let (grid, rule, marked) = (&self.grid, &self.rule, &mut self.marked);
+        let affected: Vec<Pos> = changed
+            .iter()
+            .flat_map(|&pos| iter::once(pos).chain(rule.dependents(grid, pos)))
+            .filter(|&(i, j)| !std::mem::replace(&mut marked[i][j], true))
+            .collect();
+        // Unmarking only these keeps a step proportional to the changes
+        affected.iter().for_each(|&(i, j)| marked[i][j] = false);
+        affected
+    }
+}
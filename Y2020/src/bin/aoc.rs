@@ -0,0 +1,183 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use advent_of_code_rust_2020 as aoc;
+use aoc::crypto::{self, InputKey};
+use aoc::error::{Error, Result};
+use aoc::io::try_read_file_unchecked;
+use aoc::manifest::{self, Manifest};
+
+// Maintenance commands, which are not puzzle solutions
+
+const USAGE: &str = "\
+Usage:
+  aoc input encrypt [--remove] [FILE...]  Write FILE.enc for each FILE (default: inputs/*.txt),
+                                          removing FILE with '--remove'
+  aoc input decrypt [--force] [FILE...]   Restore FILE from FILE.enc (default: inputs/*.txt.enc),
+                                          overwriting an existing FILE only with '--force'
+  aoc manifest update [FILE...]           Record hash, lines and bytes of each FILE in the
+                                          manifest next to it (default: inputs/*.txt)
+  aoc manifest check [FILE...]            Report FILEs which do not match the manifest
+  aoc manifest answers FILE PART1 [PART2] Record answers computed from FILE as it is now
+
+The passphrase is read from $AOC_INPUT_KEY, or the file at $AOC_INPUT_KEY_FILE (default: .aoc-key).";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+    let result = match args.as_slice() {
+        ["input", "encrypt", rest @ ..] => encrypt_inputs(rest),
+        ["input", "decrypt", rest @ ..] => decrypt_inputs(rest),
+        ["manifest", "update", rest @ ..] => update_manifest(rest),
+        ["manifest", "check", rest @ ..] => check_manifest(rest),
+        ["manifest", "answers", file, part1, rest @ ..] if rest.len() <= 1 => {
+            record_answers(file, part1, rest.first())
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+// Input encryption //
+
+fn encrypt_inputs(args: &[&str]) -> Result<()> {
+    let remove = args.contains(&"--remove");
+    let files: Vec<&str> = args.iter().cloned().filter(|a| *a != "--remove").collect();
+    let paths = paths_or_inputs(&files, ".txt")?;
+    let key = InputKey::load()?;
+    paths.iter().try_for_each(|path| {
+        let enc_path = crypto::encrypt_file(&key, path)?;
+        println!("{} -> {}", path.display(), enc_path.display());
+        if remove {
+            // Never lose an input which cannot be decrypted back
+            let plaintext = fs::read_to_string(path).map_err(io_error(path))?;
+            if crypto::read_encrypted(path)? != plaintext {
+                return Err(Error::Crypto {
+                    path: enc_path,
+                    reason: String::from("round trip mismatch, keeping plaintext"),
+                });
+            }
+            fs::remove_file(path).map_err(io_error(path))?;
+        }
+        Ok(())
+    })
+}
+
+fn decrypt_inputs(args: &[&str]) -> Result<()> {
+    let force = args.contains(&"--force");
+    let files: Vec<&str> = args.iter().cloned().filter(|a| *a != "--force").collect();
+    let paths: Vec<PathBuf> = if files.is_empty() {
+        paths_or_inputs(&[], ".txt.enc")?
+            .iter()
+            .map(|enc_path| enc_path.with_extension("")) // Strip '.enc'
+            .collect()
+    } else {
+        files.iter().map(PathBuf::from).collect()
+    };
+    // Checked before writing anything, so that no input is half restored
+    if let Some(path) = paths.iter().find(|path| !force && path.exists()) {
+        return Err(Error::Crypto {
+            path: path.clone(),
+            reason: String::from("already exists, use '--force' to overwrite it"),
+        });
+    }
+    paths.iter().try_for_each(|path| {
+        let plaintext = crypto::read_encrypted(path)?;
+        fs::write(path, plaintext).map_err(io_error(path))?;
+        let enc_path = crypto::encrypted_path(path);
+        println!("{} -> {}", enc_path.display(), path.display());
+        Ok(())
+    })
+}
+
+// Input manifest //
+
+fn update_manifest(args: &[&str]) -> Result<()> {
+    paths_or_inputs(args, ".txt")?.iter().try_for_each(|path| {
+        let text = try_read_file_unchecked(path)?;
+        let manifest_path = Manifest::path_for(path);
+        let mut manifest = load_or_default(&manifest_path)?;
+        manifest.update(&manifest::input_name(path), &text);
+        manifest.save(&manifest_path)?;
+        println!("{} -> {}", path.display(), manifest_path.display());
+        Ok(())
+    })
+}
+
+fn check_manifest(args: &[&str]) -> Result<()> {
+    let paths = paths_or_inputs(args, ".txt")?;
+    let mut failures = 0;
+    for path in paths.iter() {
+        let text = try_read_file_unchecked(path)?;
+        let manifest = load_or_default(&Manifest::path_for(path))?;
+        let name = manifest::input_name(path);
+        if let Some(mismatch) = manifest.check(&name, &text) {
+            println!("{}: {}", path.display(), mismatch);
+            failures += 1;
+        } else if !manifest.0.contains_key(&name) {
+            println!("{}: not in manifest", path.display());
+            failures += 1;
+        } else if manifest.stale_answers().contains(&name.as_str()) {
+            println!(
+                "{}: answers were computed from another input",
+                path.display()
+            );
+            failures += 1;
+        }
+    }
+    if failures > 0 {
+        process::exit(1);
+    }
+    Ok(())
+}
+
+fn record_answers(file: &str, part1: &str, part2: Option<&&str>) -> Result<()> {
+    let path = Path::new(file);
+    let text = try_read_file_unchecked(path)?;
+    let manifest_path = Manifest::path_for(path);
+    let mut manifest = load_or_default(&manifest_path)?;
+    let name = manifest::input_name(path);
+    manifest.set_answers(
+        &name,
+        &text,
+        Some(String::from(part1)),
+        part2.map(|s| String::from(*s)),
+    );
+    manifest.save(&manifest_path)
+}
+
+fn load_or_default(manifest_path: &Path) -> Result<Manifest> {
+    if manifest_path.exists() {
+        Manifest::load(manifest_path)
+    } else {
+        Ok(Manifest::default())
+    }
+}
+
+// Given files, or every file in 'inputs/' with 'suffix'
+fn paths_or_inputs(files: &[&str], suffix: &str) -> Result<Vec<PathBuf>> {
+    if !files.is_empty() {
+        return Ok(files.iter().map(PathBuf::from).collect());
+    }
+    let input_dir = Path::new("inputs");
+    let mut paths: Vec<PathBuf> = fs::read_dir(input_dir)
+        .map_err(io_error(input_dir))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.to_string_lossy().ends_with(suffix))
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+fn io_error(path: &Path) -> impl FnOnce(std::io::Error) -> Error {
+    let path = path.to_path_buf();
+    move |source| Error::Io { path, source }
+}
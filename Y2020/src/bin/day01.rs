@@ -1,59 +1,69 @@
-extern crate derive_more;
-use derive_more::{Add, FromStr, Mul};
-
-use std::path::Path;
-
 use advent_of_code_rust_2020 as aoc;
+use aoc::ksum::k_sum;
 
 // Type Declarations //
 
-#[derive(FromStr, Add, Mul, PartialEq, Eq, Clone, Copy)]
-#[mul(forward)]
-struct Entry(i32);
+type Entry = i64;
 
-// Part 1 //
+const DEFAULT_TARGET: Entry = 2020;
 
-fn solve1(entries: &[Entry]) -> i32 {
-    let pairs = || entries.iter().enumerate();
-    let (i, j) = pairs()
-        .find_map(|(i, &x)| {
-            pairs().find_map(|(j, &y)| {
-                if i != j && x + y == Entry(2020) {
-                    Some((i, j))
-                } else {
-                    None
-                }
-            })
+// Every distinct set of 'k' indices whose entries sum to 'target', with the product
+// of those entries, or None if it doesn't fit in an 'Entry'
+fn products(entries: &[Entry], k: usize, target: Entry) -> Vec<(Vec<usize>, Option<Entry>)> {
+    k_sum(entries, k, target)
+        .into_iter()
+        .map(|indices| {
+            let product = indices
+                .iter()
+                .try_fold(1 as Entry, |product, &i| product.checked_mul(entries[i]));
+            (indices, product)
         })
-        .expect("Should have answer");
-    (entries[i] * entries[j]).0
+        .collect()
+}
+
+fn first_product(entries: &[Entry], k: usize, target: Entry) -> Entry {
+    products(entries, k, target)
+        .first()
+        .expect("Should have answer")
+        .1
+        .expect("Product should fit in an i64")
+}
+
+// Part 1 //
+
+fn solve1(entries: &[Entry], target: Entry) -> Entry {
+    first_product(entries, 2, target)
 }
 
 // Part 2 //
 
-fn solve2(entries: &[Entry]) -> i32 {
-    let pairs = || entries.iter().enumerate();
-    let diff_3 = |i, j, k| i != j && j != k && k != i;
-    let (i, j, k) = pairs()
-        .find_map(|(i, &x)| {
-            pairs().find_map(|(j, &y)| {
-                pairs().find_map(|(k, &z)| {
-                    if diff_3(i, j, k) && x + y + z == Entry(2020) {
-                        Some((i, j, k))
-                    } else {
-                        None
-                    }
-                })
-            })
-        })
-        .expect("Should have answer");
-    (entries[i] * entries[j] * entries[k]).0
+fn solve2(entries: &[Entry], target: Entry) -> Entry {
+    first_product(entries, 3, target)
 }
 
 // I/O //
 
 fn main() {
-    let entries: Vec<Entry> = aoc::io::read_file_vec(Path::new("inputs/day01.txt"));
-    println!("{}", solve1(&entries));
-    println!("{}", solve2(&entries));
+    let entries: Vec<Entry> = aoc::io::read_file_vec(&aoc::runner::input_path("inputs/day01.txt"));
+    // '--target <n>' replaces 2020, and '--k <k>' lists every k entries summing to it,
+    // as their product and their line numbers
+    let target: Entry = aoc::runner::arg_value("--target")
+        .map(|s| s.parse().expect("--target should be an integer"))
+        .unwrap_or(DEFAULT_TARGET);
+    match aoc::runner::arg_value("--k") {
+        Some(k) => {
+            let k: usize = k.parse().expect("--k should be a non-negative integer");
+            let found = products(&entries, k, target);
+            found.iter().for_each(|(indices, product)| {
+                let lines: Vec<String> = indices.iter().map(|i| (i + 1).to_string()).collect();
+                let product = product.map_or_else(|| String::from("overflow"), |p| p.to_string());
+                println!("{} (lines {})", product, lines.join(", "));
+            });
+            eprintln!("{} sets of {} entries sum to {}", found.len(), k, target);
+        }
+        None => {
+            println!("{}", solve1(&entries, target));
+            println!("{}", solve2(&entries, target));
+        }
+    }
 }
@@ -1,88 +1,231 @@
-extern crate nom;
-use nom::character::complete::{alpha1, char, space0, space1};
-use nom::{sequence, IResult};
-
-use std::path::Path;
+use std::fmt;
+use std::fmt::Display;
+use std::fs;
 
 use advent_of_code_rust_2020 as aoc;
+use aoc::parse::AocParse;
 
 // Type Declarations //
 
-type Password = String;
-#[derive(Debug)]
+#[derive(Debug, AocParse)]
+#[aoc(format = "{num1}-{num2} {letter}")]
 struct Policy {
     letter: char,
     num1: i32,
     num2: i32,
 }
 
-// Part 1 //
+#[derive(Debug, AocParse)]
+#[aoc(format = "{policy}: {password}")]
+struct Entry {
+    policy: Policy,
+    password: String,
+}
 
-fn solve1(entries: &[(Password, Policy)]) -> i32 {
-    entries.iter().filter(is_valid_1).count() as i32
+// One interpretation of what a policy demands of a password
+trait PolicyRule {
+    fn name(&self) -> &'static str;
+    fn check(&self, policy: &Policy, password: &str) -> Verdict;
 }
 
-fn is_valid_1(entry: &&(Password, Policy)) -> bool {
-    let (password, policy) = entry;
-    let letter_count = password.chars().filter(|&c| c == policy.letter).count() as i32;
-    policy.num1 <= letter_count && letter_count <= policy.num2
+#[derive(Debug, PartialEq)]
+struct Verdict {
+    valid: bool,
+    evidence: Evidence,
 }
 
-// Part 2 //
+// What decided the verdict
+#[derive(Debug, PartialEq)]
+enum Evidence {
+    // Occurrences of the letter
+    Count(i32),
+    // Characters at the two 1-based positions, 'None' if past the end
+    Positions((i32, Option<char>), (i32, Option<char>)),
+}
+
+impl Display for Evidence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let at = |(pos, c): (i32, Option<char>)| match c {
+            Some(c) => format!("{}={:?}", pos, c),
+            None => format!("{}=none", pos),
+        };
+        match *self {
+            Evidence::Count(count) => write!(f, "count {}", count),
+            Evidence::Positions(first, second) => {
+                write!(f, "positions {} {}", at(first), at(second))
+            }
+        }
+    }
+}
+
+impl Display for Policy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{} {}", self.num1, self.num2, self.letter)
+    }
+}
 
-fn solve2(entries: &[(Password, Policy)]) -> i32 {
-    entries.iter().filter(is_valid_2).count() as i32
+fn solve(entries: &[Entry], rule: &dyn PolicyRule) -> i32 {
+    entries
+        .iter()
+        .filter(|Entry { policy, password }| rule.check(policy, password).valid)
+        .count() as i32
 }
 
-fn is_valid_2(entry: &&(Password, Policy)) -> bool {
-    let (password, policy) = entry;
-    let first = password.chars().nth((policy.num1 - 1) as usize);
-    let second = password.chars().nth((policy.num2 - 1) as usize);
-    let c = policy.letter;
-    match (first, second) {
-        (Some(x), Some(y)) if (x == c) ^ (y == c) => true,
-        _ => false,
+// Part 1 //
+
+// The letter occurs between num1 and num2 times
+struct LetterCount;
+
+impl PolicyRule for LetterCount {
+    fn name(&self) -> &'static str {
+        "count"
+    }
+
+    fn check(&self, policy: &Policy, password: &str) -> Verdict {
+        let letter_count = password.chars().filter(|&c| c == policy.letter).count() as i32;
+        Verdict {
+            valid: policy.num1 <= letter_count && letter_count <= policy.num2,
+            evidence: Evidence::Count(letter_count),
+        }
+    }
+}
+
+// Part 2 //
+
+// The letter is at exactly one of the 1-based positions num1 and num2,
+// which must both be within the password
+struct LetterPositions;
+
+impl PolicyRule for LetterPositions {
+    fn name(&self) -> &'static str {
+        "positions"
+    }
+
+    fn check(&self, policy: &Policy, password: &str) -> Verdict {
+        let at = |pos: i32| (pos, password.chars().nth((pos - 1) as usize));
+        let (first, second) = (at(policy.num1), at(policy.num2));
+        let valid = match (first.1, second.1) {
+            (Some(x), Some(y)) => (x == policy.letter) ^ (y == policy.letter),
+            _ => false,
+        };
+        Verdict {
+            valid,
+            evidence: Evidence::Positions(first, second),
+        }
     }
 }
 
 // I/O //
 
+const RULES: [&dyn PolicyRule; 2] = [&LetterCount, &LetterPositions];
+
 fn main() {
-    let lines = aoc::io::read_file_line(Path::new("inputs/day02.txt"));
-    let entries: Vec<(Password, Policy)> = lines
-        .map(|s| aoc::nom::unwrap_parsed(parse_entry(&s)))
-        .collect();
-    println!("{}", solve1(&entries));
-    println!("{}", solve2(&entries));
+    let entries: Vec<Entry> = aoc::io::read_file_vec(&aoc::runner::input_path("inputs/day02.txt"));
+    RULES
+        .iter()
+        .for_each(|&rule| println!("{}", solve(&entries, rule)));
+
+    // '--csv <path>' writes every rule's verdict on every entry, with its evidence
+    if let Some(path) = aoc::runner::arg_value("--csv") {
+        fs::write(&path, verdict_csv(&entries)).expect("CSV write error");
+    }
 }
 
-fn parse_policy<'a>(input: &'a str) -> IResult<&'a str, Policy> {
-    let err = |i: &'a str| aoc::nom::error(i, nom::error::ErrorKind::Char);
-    sequence::tuple((
-        aoc::nom::number,
-        char('-'),
-        aoc::nom::number,
-        space1,
-        alpha1,
-    ))(input)
-    .and_then(|(input, (num1, _, num2, _, alphas))| {
-        if alphas.len() != 1 {
-            Err(err(input))
-        } else {
-            let letter: char = alphas.chars().nth(0).ok_or(err(input))?;
-            Ok((
-                input,
-                Policy {
-                    letter: letter,
-                    num1: num1,
-                    num2: num2,
-                },
-            ))
-        }
-    })
+fn verdict_csv(entries: &[Entry]) -> String {
+    let rows: Vec<Vec<String>> = entries
+        .iter()
+        .enumerate()
+        .flat_map(|(i, Entry { policy, password })| {
+            RULES.iter().map(move |rule| {
+                let Verdict { valid, evidence } = rule.check(policy, password);
+                vec![
+                    (i + 1).to_string(),
+                    policy.to_string(),
+                    password.clone(),
+                    String::from(rule.name()),
+                    valid.to_string(),
+                    evidence.to_string(),
+                ]
+            })
+        })
+        .collect();
+    let header = ["line", "policy", "password", "rule", "valid", "evidence"];
+    aoc::runner::format_csv(&header, &rows)
 }
 
-fn parse_entry(input: &str) -> IResult<&str, (Password, Policy)> {
-    sequence::tuple((parse_policy, char(':'), space0, alpha1))(input)
-        .and_then(|(input, (policy, _, _, password))| Ok((input, (String::from(password), policy))))
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(rule: &dyn PolicyRule, line: &str) -> Verdict {
+        let Entry { policy, password } = line.parse().unwrap();
+        rule.check(&policy, &password)
+    }
+
+    fn verdict(valid: bool, evidence: Evidence) -> Verdict {
+        Verdict { valid, evidence }
+    }
+
+    #[test]
+    fn letter_count() {
+        let rule = LetterCount;
+        assert_eq!(
+            check(&rule, "1-3 a: abcde"),
+            verdict(true, Evidence::Count(1))
+        );
+        assert_eq!(
+            check(&rule, "1-3 b: cdefg"),
+            verdict(false, Evidence::Count(0))
+        );
+        assert_eq!(
+            check(&rule, "2-9 c: ccccccccc"),
+            verdict(true, Evidence::Count(9))
+        );
+        assert_eq!(
+            check(&rule, "2-3 c: cccc"),
+            verdict(false, Evidence::Count(4))
+        );
+    }
+
+    #[test]
+    fn letter_positions() {
+        let rule = LetterPositions;
+        let positions = |c1, c2| Evidence::Positions((1, c1), (3, c2));
+        assert_eq!(
+            check(&rule, "1-3 a: abcde"),
+            verdict(true, positions(Some('a'), Some('c')))
+        );
+        assert_eq!(
+            check(&rule, "1-3 b: cdefg"),
+            verdict(false, positions(Some('c'), Some('e')))
+        );
+        assert_eq!(
+            check(&rule, "1-3 c: cccc"),
+            verdict(false, positions(Some('c'), Some('c')))
+        );
+        // Both positions must exist, even if the letter is at the other one
+        assert_eq!(
+            check(&rule, "1-3 a: a"),
+            verdict(false, positions(Some('a'), None))
+        );
+        assert_eq!(
+            check(&rule, "1-3 a: ab"),
+            verdict(false, positions(Some('a'), None))
+        );
+        let verdict = check(&rule, "2-9 c: ccccccccc");
+        assert!(!verdict.valid);
+        assert_eq!(verdict.evidence.to_string(), "positions 2='c' 9='c'");
+    }
+
+    #[test]
+    fn csv() {
+        let entries: Vec<Entry> = vec!["1-3 a: abcde".parse().unwrap()];
+        let csv = verdict_csv(&entries);
+        let expected = "\
+line,policy,password,rule,valid,evidence
+1,1-3 a,abcde,count,true,count 1
+1,1-3 a,abcde,positions,true,positions 1='a' 3='c'
+";
+        assert_eq!(csv, expected);
+    }
 }
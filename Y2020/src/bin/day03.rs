@@ -2,7 +2,6 @@ extern crate derive_more;
 
 use std::fmt::Display;
 use std::fmt::Formatter;
-use std::path::Path;
 
 use advent_of_code_rust_2020 as aoc;
 
@@ -74,7 +73,7 @@ fn solve2(forest: &Forest) -> i64 {
 // I/O //
 
 fn main() {
-    let forest: Forest = aoc::io::read_file_grid(Path::new("inputs/day03.txt"));
+    let forest: Forest = aoc::io::read_file_grid(&aoc::runner::input_path("inputs/day03.txt"));
     println!("{}", solve1(&forest));
     println!("{}", solve2(&forest));
 }
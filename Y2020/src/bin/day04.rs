@@ -1,157 +1,207 @@
-extern crate derive_more;
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Display;
 
-use nom::branch::{alt, permutation};
-use nom::bytes::complete::tag;
-use nom::character::complete::{char, multispace0, none_of, one_of};
-use nom::combinator::{all_consuming, opt};
-use nom::multi::{many1, many_m_n};
-use nom::{sequence, IResult};
-
-use std::fs::File;
-use std::io::Read;
-use std::path::Path;
+use nom::multi::many1;
 
 use advent_of_code_rust_2020 as aoc;
+use aoc::error::RecordError;
+use aoc::io::Input;
+use aoc::nom::ParseResult;
+use aoc::validate::{Field, Rule, Schema, Violation, ViolationKind, Warning};
 
 // Type Declarations //
 
+// Field values by key, e.g. "byr" -> "1937"
+type Passport<'a> = HashMap<&'a str, &'a str>;
+
+// Why a passport fails validation, and where it is in the input
 #[derive(Debug)]
-struct Passport {
-    birth_year: String,
-    issue_year: String,
-    expiration_year: String,
-    height: String,
-    hair_color: String,
-    eye_color: String,
-    passport_id: String,
-    country_id: Option<String>,
+struct PassportReport {
+    index: usize, // 1-based, in input order
+    line: usize,  // 1-based, where the passport starts
+    missing: Vec<&'static str>,
+    invalid: Vec<Violation>,
+    warnings: Vec<Warning>, // Unknown or repeated keys, which don't fail validation
+    unreadable: Option<RecordError>, // Set when the passport can't be parsed at all
 }
 
-// Part 1 //
+impl PassportReport {
+    fn unreadable(index: usize, line: usize, error: RecordError) -> Self {
+        PassportReport {
+            index,
+            line,
+            missing: vec![],
+            invalid: vec![],
+            warnings: vec![],
+            unreadable: Some(error),
+        }
+    }
 
-fn solve1(passport_strings: &Vec<&str>) -> i32 {
-    let passports: Vec<Passport> = passport_strings
-        .iter()
-        .filter_map(|ps| parse_passport(ps).ok().map(|(_, ps)| ps))
-        .collect();
-    passports.len() as i32
+    fn has_required(&self) -> bool {
+        self.unreadable.is_none() && self.missing.is_empty()
+    }
+
+    fn is_valid(&self) -> bool {
+        self.has_required() && self.invalid.is_empty()
+    }
 }
 
-fn parse_passport<'a>(input: &'a str) -> IResult<&'a str, Passport> {
-    all_consuming(permutation((
-        parse_data("byr"),
-        parse_data("iyr"),
-        parse_data("eyr"),
-        parse_data("hgt"),
-        parse_data("hcl"),
-        parse_data("ecl"),
-        parse_data("pid"),
-        opt(parse_data("cid")),
-    )))(input)
-    .and_then(|(input, (byr, iyr, eyr, hgt, hcl, ecl, pid, cid))| {
-        Ok((
-            input,
-            Passport {
-                birth_year: byr,
-                issue_year: iyr,
-                expiration_year: eyr,
-                height: hgt,
-                hair_color: hcl,
-                eye_color: ecl,
-                passport_id: pid,
-                country_id: cid,
-            },
-        ))
-    })
+impl Display for PassportReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(error) = &self.unreadable {
+            return write!(
+                f,
+                "passport {} (line {}): cannot parse, {}",
+                self.index, self.line, error
+            );
+        }
+        let missing =
+            (!self.missing.is_empty()).then(|| format!("missing {}", self.missing.join(", ")));
+        let invalid = self.invalid.iter().filter_map(|v| match &v.kind {
+            ViolationKind::Invalid { value, reason } => {
+                Some(format!("{} {:?} ({})", v.field, value, reason))
+            }
+            ViolationKind::Missing => None,
+        });
+        let problems: Vec<String> = missing.into_iter().chain(invalid).collect();
+        write!(
+            f,
+            "passport {} (line {}): {}",
+            self.index,
+            self.line,
+            problems.join("; ")
+        )
+    }
 }
 
-// Parses /key:[^\s]+\s*/
-fn parse_data(key: &'static str) -> impl Fn(&str) -> IResult<&str, String> {
-    let to_str = |v: Vec<char>| -> String { v.iter().collect() };
-    move |input| {
-        sequence::tuple((tag(key), char(':'), many1(none_of(" \n")), multispace0))(input)
-            .and_then(|(input, (_, _, value, _))| Ok((input, to_str(value))))
+const PASSPORT_SCHEMA: Schema = Schema(&[
+    Field::required("byr", &[Rule::YearRange(1920, 2002)]),
+    Field::required("iyr", &[Rule::YearRange(2010, 2020)]),
+    Field::required("eyr", &[Rule::YearRange(2020, 2030)]),
+    Field::required(
+        "hgt",
+        &[Rule::NumberWithUnit(&[("cm", 150, 193), ("in", 59, 76)])],
+    ),
+    Field::required("hcl", &[Rule::HexColor]),
+    Field::required(
+        "ecl",
+        &[Rule::OneOf(&[
+            "amb", "blu", "brn", "gry", "grn", "hzl", "oth",
+        ])],
+    ),
+    Field::required("pid", &[Rule::Digits(9)]),
+    Field::optional("cid", &[]),
+]);
+
+fn validate(index: usize, line: usize, pairs: &[(&str, &str)]) -> PassportReport {
+    let (passport, warnings): (Passport, _) = PASSPORT_SCHEMA.collect(pairs);
+    let (missing, invalid): (Vec<Violation>, Vec<Violation>) = PASSPORT_SCHEMA
+        .validate(&passport)
+        .into_iter()
+        .partition(|v| v.kind == ViolationKind::Missing);
+    PassportReport {
+        index,
+        line,
+        missing: missing.iter().map(|v| v.field).collect(),
+        invalid,
+        warnings,
+        unreadable: None,
     }
 }
 
-// Part 2 //
+// Part 1 //
 
-fn solve2(passport_strings: &Vec<&str>) -> i32 {
-    let passports: Vec<Passport> = passport_strings
-        .iter()
-        .filter_map(|ps| parse_passport(ps).ok().map(|(_, ps)| ps))
-        .filter(|ps| passport_is_valid(&ps))
-        .collect();
-    passports.len() as i32
+// Every required field is present
+fn solve1(reports: &[PassportReport]) -> i32 {
+    reports.iter().filter(|r| r.has_required()).count() as i32
 }
 
-fn passport_is_valid(ps: &Passport) -> bool {
-    let guarding = || {
-        // Exploit '?' syntax of Option<()>
-        // rather than 'if b { false }';
-        let range =
-            |s: &str, min, max| s.parse().ok().filter(|&x| min <= x && x <= max).map(|_| ());
-        range(&ps.birth_year, 1920, 2002)?;
-        range(&ps.issue_year, 2010, 2020)?;
-        range(&ps.expiration_year, 2020, 2030)?;
-        parse_height(&ps.height)
-            .ok()
-            .filter(|(_, height)| match height {
-                Height::Cm(h) => 150 <= *h && *h <= 193,
-                Height::In(h) => 59 <= *h && *h <= 76,
-            })?;
-        parse_hair_color(&ps.hair_color).ok()?;
-        parse_eye_color(&ps.eye_color).ok()?;
-        parse_passport_id(&ps.passport_id).ok()?;
-        Some(())
-    };
-    guarding().is_some()
-}
+// Part 2 //
 
-enum Height {
-    Cm(i32),
-    In(i32),
+// Every required field is present and valid
+fn solve2(reports: &[PassportReport]) -> i32 {
+    reports.iter().filter(|r| r.is_valid()).count() as i32
 }
 
-fn parse_height(input: &str) -> IResult<&str, Height> {
-    aoc::nom::number(input).and_then(|(input, num)| match input {
-        "cm" => Ok(("", Height::Cm(num))),
-        "in" => Ok(("", Height::In(num))),
-        _ => Err(aoc::nom::error(input, nom::error::ErrorKind::Char)),
-    })
-}
+// I/O //
 
-fn parse_hair_color(input: &str) -> IResult<&str, String> {
-    let to_str = |v: Vec<char>| -> String { v.iter().collect() };
-    sequence::tuple((char('#'), many_m_n(6, 6, one_of("0123456789abcdef"))))(input)
-        .and_then(|(input, (_, v))| Ok((input, to_str(v))))
+fn main() {
+    let input = Input::read(&aoc::runner::input_path("inputs/day04.txt"));
+    // A passport which can't be parsed is reported and counted as invalid
+    let reports: Vec<PassportReport> = input
+        .blocks()
+        .into_iter()
+        .enumerate()
+        .map(|(i, block)| {
+            let line = input.line_number(block).expect("Block of the input");
+            match parse_block(input.as_str(), block) {
+                Ok(pairs) => validate(i + 1, line, &pairs),
+                Err(error) => PassportReport::unreadable(i + 1, line, error),
+            }
+        })
+        .collect();
+    reports
+        .iter()
+        .filter(|r| r.unreadable.is_some())
+        .for_each(|r| eprintln!("warning: {}", r));
+    reports.iter().for_each(|r| {
+        r.warnings
+            .iter()
+            .for_each(|w| eprintln!("warning: passport {} (line {}): {}", r.index, r.line, w))
+    });
+    println!("{}", solve1(&reports));
+    println!("{}", solve2(&reports));
+
+    // '--report' lists failing passports and counts failures by field
+    if aoc::runner::has_flag("--report") {
+        print_report(&reports);
+    }
 }
 
-fn parse_eye_color(input: &str) -> IResult<&str, &str> {
-    alt((
-        tag("amb"),
-        tag("blu"),
-        tag("brn"),
-        tag("gry"),
-        tag("grn"),
-        tag("hzl"),
-        tag("oth"),
-    ))(input)
+// Whitespace-separated 'key:value' pairs, in any order and with any keys
+fn parse_passport(input: &str) -> ParseResult<'_, Vec<(&str, &str)>> {
+    many1(aoc::nom::ws(aoc::nom::key_value(aoc::nom::token)))(input)
 }
 
-fn parse_passport_id(input: &str) -> IResult<&str, Vec<char>> {
-    many_m_n(9, 9, one_of("0123456789"))(input)
+// 'block' is a slice of 'text', so that errors are located by their line in the whole input
+fn parse_block<'a>(text: &'a str, block: &'a str) -> Result<Vec<(&'a str, &'a str)>, RecordError> {
+    match parse_passport(block) {
+        Ok((rest, pairs)) if rest.trim().is_empty() => Ok(pairs),
+        Ok((rest, _)) => {
+            let offset = aoc::nom::offset(text, rest).unwrap_or(0);
+            Err(RecordError::at(text, offset, "unexpected trailing input"))
+        }
+        Err(e) => Err(aoc::nom::report(text, &e)),
+    }
 }
 
-// I/O //
+fn print_report(reports: &[PassportReport]) {
+    reports
+        .iter()
+        .filter(|r| !r.is_valid())
+        .for_each(|r| eprintln!("{}", r));
 
-fn main() {
-    let mut input = String::new();
-    File::open(Path::new("inputs/day04.txt"))
-        .expect("File open error")
-        .read_to_string(&mut input)
-        .expect("File read error");
-    let passport_strings: Vec<&str> = input.split("\n\n").collect();
-    println!("{}", solve1(&passport_strings));
-    println!("{}", solve2(&passport_strings));
+    let rows: Vec<Vec<String>> = PASSPORT_SCHEMA
+        .0
+        .iter()
+        .map(|field| {
+            let missing = reports.iter().filter(|r| r.missing.contains(&field.name));
+            let invalid = reports
+                .iter()
+                .filter(|r| r.invalid.iter().any(|v| v.field == field.name));
+            vec![
+                String::from(field.name),
+                missing.count().to_string(),
+                invalid.count().to_string(),
+            ]
+        })
+        .collect();
+    eprintln!();
+    eprint!(
+        "{}",
+        aoc::runner::format_table(&["field", "missing", "invalid"], &rows)
+    );
+    let valid = reports.iter().filter(|r| r.is_valid()).count();
+    eprintln!("{} of {} passports valid", valid, reports.len());
 }
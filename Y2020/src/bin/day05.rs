@@ -1,103 +1,258 @@
-extern crate derive_more;
-use derive_more::Into;
-
-use std::convert::Into;
-use std::path::Path;
+use std::collections::HashSet;
 
 use advent_of_code_rust_2020 as aoc;
 
 // Type Declarations //
 
-type SeatBin = Vec<Dir>;
-#[derive(Debug)]
-struct SeatPos(i32, i32);
-#[derive(Into, PartialEq, Eq, PartialOrd, Ord)]
-struct SeatId(i32);
-
-enum Dir {
-    Front,
-    Back,
-    Left,
-    Right,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct SeatPos(u32, u32);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct SeatId(u32);
+
+// Boarding passes of a plane with 2^row_bits rows and 2^col_bits columns.
+// A pass is the binary row ('F' = 0, 'B' = 1) followed by the binary column ('L' = 0, 'R' = 1).
+#[derive(Debug, Clone, Copy)]
+struct SeatCodec {
+    row_bits: u32,
+    col_bits: u32,
 }
 
-impl From<char> for Dir {
-    fn from(c: char) -> Self {
-        match c {
-            'F' => Dir::Front,
-            'B' => Dir::Back,
-            'L' => Dir::Left,
-            'R' => Dir::Right,
-            _ => panic!("Invalid tile"),
+impl SeatCodec {
+    // Seat ids are u32, so a pass has at most 31 bits
+    fn new(row_bits: u32, col_bits: u32) -> Result<Self, String> {
+        match row_bits.checked_add(col_bits) {
+            Some(total) if total < 32 => Ok(SeatCodec { row_bits, col_bits }),
+            _ => Err(format!(
+                "{} row bits and {} column bits are more than 31 in total",
+                row_bits, col_bits
+            )),
         }
     }
-}
 
-// Part 1 //
+    fn rows(&self) -> u32 {
+        1 << self.row_bits
+    }
 
-fn solve1(seat_bins: &Vec<SeatBin>) -> i32 {
-    seat_bins
-        .iter()
-        .map(bin_to_pos)
-        .map(pos_to_id)
-        .max()
-        .unwrap()
-        .0
+    fn cols(&self) -> u32 {
+        1 << self.col_bits
+    }
+
+    fn decode(&self, pass: &str) -> Result<SeatPos, String> {
+        let len = (self.row_bits + self.col_bits) as usize;
+        if pass.chars().count() != len {
+            return Err(format!("expected {} characters, got {:?}", len, pass));
+        }
+        // Split by characters, not bytes, so that a non-ASCII character is reported by 'bits'
+        let split = pass
+            .char_indices()
+            .nth(self.row_bits as usize)
+            .map_or(pass.len(), |(i, _)| i);
+        let (row, col) = pass.split_at(split);
+        Ok(SeatPos(bits(row, 'F', 'B')?, bits(col, 'L', 'R')?))
+    }
+
+    fn encode(&self, SeatPos(row, col): SeatPos) -> String {
+        let chars = |n: u32, len: u32, zero: char, one: char| {
+            (0..len)
+                .rev()
+                .map(move |i| if n >> i & 1 == 1 { one } else { zero })
+        };
+        chars(row, self.row_bits, 'F', 'B')
+            .chain(chars(col, self.col_bits, 'L', 'R'))
+            .collect()
+    }
+
+    fn id(&self, SeatPos(row, col): SeatPos) -> SeatId {
+        SeatId(row << self.col_bits | col)
+    }
+
+    fn pos(&self, SeatId(id): SeatId) -> SeatPos {
+        SeatPos(id >> self.col_bits, id & (self.cols() - 1))
+    }
+
+    // Taken seats as '#' and free ones as '.', a line per row
+    fn chart(&self, taken: &HashSet<SeatPos>, mark: Option<SeatPos>) -> String {
+        let width = (self.rows() - 1).to_string().len();
+        (0..self.rows())
+            .map(|row| {
+                let seats: String = (0..self.cols())
+                    .map(|col| match SeatPos(row, col) {
+                        pos if Some(pos) == mark => 'O',
+                        pos if taken.contains(&pos) => '#',
+                        _ => '.',
+                    })
+                    .collect();
+                format!("{:>width$} {}\n", row, seats, width = width)
+            })
+            .collect()
+    }
 }
 
-fn bin_to_pos(bin: &SeatBin) -> SeatPos {
-    // 0-index
-    let (mut row_min, mut row_max) = (0, 127);
-    let (mut col_min, mut col_max) = (0, 7);
-    bin.iter().for_each(|dir| match dir {
-        Dir::Front => row_max = row_min + (row_max - row_min) / 2,
-        Dir::Back => row_min = row_max - (row_max - row_min) / 2,
-        Dir::Left => col_max = col_min + (col_max - col_min) / 2,
-        Dir::Right => col_min = col_max - (col_max - col_min) / 2,
-    });
-    assert_eq!(row_min, row_max);
-    assert_eq!(col_min, col_max);
-    SeatPos(row_min, col_min)
+// Binary number of 'zero' and 'one' digits, most significant first
+fn bits(s: &str, zero: char, one: char) -> Result<u32, String> {
+    s.chars().try_fold(0, |n, c| match c {
+        c if c == zero => Ok(n << 1),
+        c if c == one => Ok(n << 1 | 1),
+        _ => Err(format!("expected '{}' or '{}', got {:?}", zero, one, c)),
+    })
 }
 
-fn pos_to_id(pos: SeatPos) -> SeatId {
-    SeatId(8 * pos.0 + pos.1)
+// Part 1 //
+
+fn solve1(codec: &SeatCodec, seats: &[SeatPos]) -> u32 {
+    seats.iter().map(|&pos| codec.id(pos)).max().unwrap().0
 }
 
 // Part 2 //
 
-fn solve2(seat_bins: &Vec<SeatBin>) -> i32 {
-    let ids: Vec<_> = seat_bins.iter().map(bin_to_pos).map(pos_to_id).collect();
-    find_my_seat(ids).0
+fn solve2(codec: &SeatCodec, seats: &[SeatPos]) -> Option<u32> {
+    find_my_seat(codec, seats).map(|id| id.0)
 }
 
-fn find_my_seat(ids: Vec<SeatId>) -> SeatId {
-    let mut seats_reserved = [false; 8 * 128];
-
-    for id in ids.into_iter() {
-        seats_reserved[i32::from(id) as usize] = true;
-    }
-    let (my_seat_id, _) = seats_reserved
-        .iter()
-        .enumerate()
-        .skip_while(|&(_, &r)| r == false) // Skip empty back seats
-        .skip_while(|&(_, &r)| r == true) // Find non-reserved seat
-        .next()
-        .unwrap();
-    SeatId(my_seat_id as i32)
+// The free seat whose neighbouring ids are both taken
+fn find_my_seat(codec: &SeatCodec, seats: &[SeatPos]) -> Option<SeatId> {
+    let taken: HashSet<SeatId> = seats.iter().map(|&pos| codec.id(pos)).collect();
+    (1..codec.rows() * codec.cols() - 1)
+        .map(SeatId)
+        .find(|&SeatId(id)| {
+            !taken.contains(&SeatId(id))
+                && taken.contains(&SeatId(id - 1))
+                && taken.contains(&SeatId(id + 1))
+        })
 }
 
 // I/O //
 
 fn main() {
-    let seat_bins: Vec<SeatBin> = aoc::io::read_file_line(Path::new("inputs/day05.txt"))
-        .map(parse_seat)
+    // '--row-bits <n>' and '--col-bits <n>' describe another plane than 128 x 8
+    let bits_arg = |name: &str, default: u32| {
+        aoc::runner::arg_value(name)
+            .map(|s| s.parse().expect("Bit count should be an integer"))
+            .unwrap_or(default)
+    };
+    let codec = SeatCodec::new(bits_arg("--row-bits", 7), bits_arg("--col-bits", 3))
+        .unwrap_or_else(|e| panic!("{}", e));
+    let seats: Vec<SeatPos> = aoc::io::read_file_line(&aoc::runner::input_path("inputs/day05.txt"))
+        .enumerate()
+        .map(|(i, line)| {
+            codec
+                .decode(&line)
+                .unwrap_or_else(|e| panic!("line {}: {}", i + 1, e))
+        })
         .collect();
-    println!("{}", solve1(&seat_bins));
-    println!("{}", solve2(&seat_bins));
+    println!("{}", solve1(&codec, &seats));
+    match solve2(&codec, &seats) {
+        Some(id) => println!("{}", id),
+        None => println!("no free seat"),
+    }
+
+    // '--chart' draws the plane, with my seat as 'O'
+    if aoc::runner::has_flag("--chart") {
+        let my_pos = find_my_seat(&codec, &seats).map(|id| codec.pos(id));
+        eprint!("{}", codec.chart(&seats.iter().copied().collect(), my_pos));
+        if let Some(pos) = my_pos {
+            eprintln!(
+                "my seat: {} (row {}, column {})",
+                codec.encode(pos),
+                pos.0,
+                pos.1
+            );
+        }
+    }
 }
 
-fn parse_seat(s: String) -> Vec<Dir> {
-    assert_eq!(s.len(), 10);
-    s.chars().map(Into::into).take(10).collect()
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn default_codec() -> SeatCodec {
+        SeatCodec::new(7, 3).unwrap()
+    }
+
+    #[test]
+    fn example_passes() {
+        let codec = default_codec();
+        let examples = [
+            ("FBFBBFFRLR", 44, 5, 357),
+            ("BFFFBBFRRR", 70, 7, 567),
+            ("FFFBBBFRRR", 14, 7, 119),
+            ("BBFFBBFRLL", 102, 4, 820),
+        ];
+        for &(pass, row, col, id) in examples.iter() {
+            let pos = codec.decode(pass).unwrap();
+            assert_eq!(pos, SeatPos(row, col));
+            assert_eq!(codec.id(pos), SeatId(id));
+            assert_eq!(codec.pos(SeatId(id)), pos);
+            assert_eq!(codec.encode(pos), pass);
+        }
+    }
+
+    #[test]
+    fn round_trip_other_planes() {
+        for &(row_bits, col_bits) in [(0, 1), (1, 0), (2, 5), (4, 4), (16, 15), (31, 0)].iter() {
+            let codec = SeatCodec::new(row_bits, col_bits).unwrap();
+            let (rows, cols) = (codec.rows(), codec.cols());
+            let corners = [
+                (0, 0),
+                (rows - 1, 0),
+                (0, cols - 1),
+                (rows - 1, cols - 1),
+                (rows / 2, cols / 2),
+            ];
+            for &(row, col) in corners.iter() {
+                let pos = SeatPos(row, col);
+                let pass = codec.encode(pos);
+                assert_eq!(pass.len(), (row_bits + col_bits) as usize);
+                assert_eq!(codec.decode(&pass), Ok(pos), "{}", pass);
+                assert_eq!(codec.pos(codec.id(pos)), pos);
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_wide_codecs() {
+        assert!(SeatCodec::new(16, 15).is_ok());
+        assert!(SeatCodec::new(31, 0).is_ok());
+        assert!(SeatCodec::new(16, 16).is_err());
+        assert!(SeatCodec::new(32, 0).is_err());
+        assert!(SeatCodec::new(u32::MAX, 1).is_err());
+    }
+
+    #[test]
+    fn rejects_bad_passes() {
+        let codec = default_codec();
+        assert!(codec.decode("FBFBBFFRL").is_err());
+        assert!(codec.decode("FBFBBFFRLRR").is_err());
+        assert_eq!(
+            codec.decode("FBFBBFFRLX"),
+            Err(String::from("expected 'L' or 'R', got 'X'"))
+        );
+        assert_eq!(
+            codec.decode("FBFBBFLRLR"),
+            Err(String::from("expected 'F' or 'B', got 'L'"))
+        );
+        // 10 bytes, but 9 characters
+        assert!(codec.decode("FFFFFFéLR").is_err());
+        assert_eq!(
+            codec.decode("FFFFFFéLRL"),
+            Err(String::from("expected 'F' or 'B', got 'é'"))
+        );
+    }
+
+    #[test]
+    fn my_seat() {
+        let codec = SeatCodec::new(2, 2).unwrap();
+        let seats =
+            |ids: &[u32]| -> Vec<SeatPos> { ids.iter().map(|&id| codec.pos(SeatId(id))).collect() };
+        assert_eq!(find_my_seat(&codec, &seats(&[3, 4, 6, 7])), Some(SeatId(5)));
+        // Free seats without both neighbors taken
+        assert_eq!(find_my_seat(&codec, &seats(&[0, 1, 2, 3])), None);
+        assert_eq!(find_my_seat(&codec, &seats(&[4, 5, 6, 9])), None);
+        assert_eq!(
+            find_my_seat(&codec, &seats(&(0..16).collect::<Vec<u32>>())),
+            None
+        );
+        assert_eq!(find_my_seat(&codec, &[]), None);
+        assert_eq!(solve2(&codec, &seats(&[7, 9])), Some(8));
+    }
 }
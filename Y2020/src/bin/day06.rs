@@ -1,74 +1,259 @@
 extern crate derive_more;
+use derive_more::{BitAnd, BitOr, BitXor};
 
-extern crate itertools;
-use itertools::Itertools;
-
-use std::collections::HashSet;
-use std::path::Path;
+use nom::bytes::complete::take_till1;
+use nom::character::complete::line_ending;
+use nom::error::{ContextError, ErrorKind, ParseError};
+use nom::multi::separated_list1;
 
 use advent_of_code_rust_2020 as aoc;
+use aoc::nom::{Error, ParseResult};
 
 // Type Declarations //
 
-type Answers = Vec<char>;
-type GroupAnswers = Vec<Answers>;
+const QUESTIONS: u32 = 26;
 
-// Part 1 //
+// Questions answered "yes", as bit (c - 'a') for question c
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BitAnd, BitOr, BitXor)]
+struct AnswerSet(u32);
 
-fn solve1(group_answers: &Vec<GroupAnswers>) -> i32 {
-    group_answers
-        .iter()
-        .map(collect_group_answers)
-        .map(|ans| ans.len() as i32)
-        .sum()
+// One answer set per person
+type Group = Vec<AnswerSet>;
+
+impl AnswerSet {
+    const NONE: AnswerSet = AnswerSet(0);
+    const ALL: AnswerSet = AnswerSet((1 << QUESTIONS) - 1);
+
+    // 'None' unless 'question' is in a-z
+    fn with(self, question: char) -> Option<Self> {
+        if question.is_ascii_lowercase() {
+            Some(AnswerSet(self.0 | 1 << (question as u32 - 'a' as u32)))
+        } else {
+            None
+        }
+    }
+
+    fn contains(self, question: char) -> bool {
+        self.with(question) == Some(self)
+    }
+
+    fn len(self) -> u32 {
+        self.0.count_ones()
+    }
 }
 
-fn collect_group_answers(group_answer: &GroupAnswers) -> Answers {
-    let mut answer_set = HashSet::new();
-    group_answer.iter().for_each(|answers| {
-        answers.iter().for_each(|&c| {
-            answer_set.insert(c);
-        })
+// Questions anyone answered
+fn union(group: &[AnswerSet]) -> AnswerSet {
+    group.iter().fold(AnswerSet::NONE, |acc, &set| acc | set)
+}
+
+// Questions everyone answered
+fn intersection(group: &[AnswerSet]) -> AnswerSet {
+    group.iter().fold(AnswerSet::ALL, |acc, &set| acc & set)
+}
+
+// Questions an odd number of people answered
+fn symmetric_difference(group: &[AnswerSet]) -> AnswerSet {
+    group.iter().fold(AnswerSet::NONE, |acc, &set| acc ^ set)
+}
+
+// Number of people who answered each question, indexed by (c - 'a')
+fn histogram(group: &[AnswerSet]) -> [u32; QUESTIONS as usize] {
+    let mut counts = [0; QUESTIONS as usize];
+    group.iter().for_each(|&AnswerSet(bits)| {
+        counts
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, count)| *count += bits >> i & 1)
     });
-    answer_set.into_iter().collect()
+    counts
+}
+
+// Questions answered by at least 'k' people
+fn at_least(group: &[AnswerSet], k: u32) -> AnswerSet {
+    let bits = histogram(group)
+        .iter()
+        .enumerate()
+        .filter(|&(_, &count)| count >= k)
+        .fold(0, |bits, (i, _)| bits | 1 << i);
+    AnswerSet(bits)
+}
+
+fn total(groups: &[Group], set_of: impl Fn(&[AnswerSet]) -> AnswerSet) -> u32 {
+    groups.iter().map(|group| set_of(group).len()).sum()
+}
+
+// Part 1 //
+
+fn solve1(groups: &[Group]) -> u32 {
+    total(groups, union)
 }
 
 // Part 2 //
 
-fn solve2(group_answers: &Vec<GroupAnswers>) -> i32 {
-    group_answers
-        .iter()
-        .map(collect_group_answers_2)
-        .map(|ans| ans.len() as i32)
-        .sum()
+fn solve2(groups: &[Group]) -> u32 {
+    total(groups, intersection)
 }
 
-fn collect_group_answers_2(group_answer: &GroupAnswers) -> Answers {
-    let to_set = |vec: &Vec<char>| -> HashSet<char> { vec.into_iter().cloned().collect() };
+// I/O //
+
+fn main() {
+    let input = aoc::io::Input::read(&aoc::runner::input_path("inputs/day06.txt"));
+    let groups: Vec<Group> = input.section_records(parse_group);
+    println!("{}", solve1(&groups));
+    println!("{}", solve2(&groups));
 
-    let group_answer_sets: HashSet<char> = group_answer
+    // '--stats' sums every group operation, with '--at-least <k>' people (default 2)
+    if aoc::runner::has_flag("--stats") {
+        print_stats(&groups);
+    }
+    // '--histogram' counts the people and groups answering each question
+    if aoc::runner::has_flag("--histogram") {
+        print_histogram(&groups);
+    }
+}
+
+fn print_stats(groups: &[Group]) {
+    let k: u32 = aoc::runner::arg_value("--at-least")
+        .map(|s| s.parse().expect("--at-least should be an integer"))
+        .unwrap_or(2);
+    let rows: Vec<Vec<String>> = vec![
+        ("anyone", total(groups, union)),
+        ("everyone", total(groups, intersection)),
+        ("an odd number", total(groups, symmetric_difference)),
+        (
+            &format!("at least {}", k),
+            total(groups, |group| at_least(group, k)),
+        ),
+    ]
+    .into_iter()
+    .map(|(who, sum)| vec![String::from(who), sum.to_string()])
+    .collect();
+    eprint!(
+        "{}",
+        aoc::runner::format_table(&["answered by", "questions"], &rows)
+    );
+}
+
+fn print_histogram(groups: &[Group]) {
+    let people = groups
         .iter()
-        .map(to_set)
-        .fold1(|answer_set, x_answer_set| {
-            // Compute intersection of answer sets
-            answer_set.intersection(&x_answer_set).cloned().collect()
+        .fold([0; QUESTIONS as usize], |mut acc, group| {
+            acc.iter_mut()
+                .zip(histogram(group).iter())
+                .for_each(|(a, n)| *a += n);
+            acc
+        });
+    let rows: Vec<Vec<String>> = ('a'..='z')
+        .zip(people.iter())
+        .map(|(question, count)| {
+            let in_groups = groups
+                .iter()
+                .filter(|group| union(group).contains(question))
+                .count();
+            vec![
+                question.to_string(),
+                count.to_string(),
+                in_groups.to_string(),
+            ]
         })
-        .expect("'group_answer' must not be empty");
-    group_answer_sets.into_iter().collect()
+        .collect();
+    eprint!(
+        "{}",
+        aoc::runner::format_table(&["question", "people", "groups"], &rows)
+    );
 }
 
-// I/O //
+// A line of a-z per person
+fn parse_group(input: &str) -> ParseResult<'_, Group> {
+    separated_list1(line_ending, parse_answers)(input)
+}
 
-fn main() {
-    let group_answers: Vec<GroupAnswers> =
-        aoc::io::read_file_blankline(Path::new("inputs/day06.txt"))
-            .map(parse_group_answers)
-            .collect();
-    println!("{}", solve1(&group_answers));
-    println!("{}", solve2(&group_answers));
+fn parse_answers(input: &str) -> ParseResult<'_, AnswerSet> {
+    let (rest, line) = take_till1(|c| c == '\n' || c == '\r')(input)?;
+    line.char_indices()
+        .try_fold(AnswerSet::NONE, |set, (i, c)| set.with(c).ok_or(i))
+        .map(|set| (rest, set))
+        .map_err(|i| {
+            let error = Error::from_error_kind(&line[i..], ErrorKind::Satisfy);
+            nom::Err::Failure(Error::add_context(&line[i..], "question (a-z)", error))
+        })
 }
 
-fn parse_group_answers(s: String) -> GroupAnswers {
-    // GroupAnswers == Vec<Vec<char>>
-    s.split("\n").map(|line| line.chars().collect()).collect()
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &[&str] = &["abc", "a\nb\nc", "ab\nac", "a\na\na\na", "b"];
+
+    fn group(text: &str) -> Group {
+        aoc::nom::unwrap_parsed(text, parse_group(text))
+    }
+
+    fn set(questions: &str) -> AnswerSet {
+        questions
+            .chars()
+            .fold(AnswerSet::NONE, |set, c| set.with(c).unwrap())
+    }
+
+    #[test]
+    fn example() {
+        let groups: Vec<Group> = EXAMPLE.iter().map(|text| group(text)).collect();
+        assert_eq!(solve1(&groups), 11);
+        assert_eq!(solve2(&groups), 6);
+    }
+
+    #[test]
+    fn at_least_matches_union_and_intersection() {
+        for text in EXAMPLE
+            .iter()
+            .chain(["abcx\nabcy\nabcz\nz", "xyz\nzyx"].iter())
+        {
+            let group = group(text);
+            let n = group.len() as u32;
+            assert_eq!(at_least(&group, 0), AnswerSet::ALL, "{:?}", text);
+            assert_eq!(at_least(&group, 1), union(&group), "{:?}", text);
+            assert_eq!(at_least(&group, n), intersection(&group), "{:?}", text);
+            assert_eq!(at_least(&group, n + 1), AnswerSet::NONE, "{:?}", text);
+        }
+        let group = group("abcx\nabcy\nabcz\nz");
+        assert_eq!(at_least(&group, 2), set("abcz"));
+        assert_eq!(at_least(&group, 3), set("abc"));
+    }
+
+    #[test]
+    fn histogram_and_symmetric_difference() {
+        let group = group("abcx\nabcy\nabcz\nz");
+        let counts = histogram(&group);
+        assert_eq!(&counts[..3], &[3, 3, 3]);
+        assert_eq!(&counts[23..], &[1, 1, 2]);
+        assert_eq!(counts.iter().sum::<u32>(), 13);
+        // Answered by 1 or 3 people
+        assert_eq!(symmetric_difference(&group), set("abcxy"));
+        assert_eq!(symmetric_difference(&[]), AnswerSet::NONE);
+        assert_eq!(intersection(&[]), AnswerSet::ALL);
+    }
+
+    #[test]
+    fn answer_sets() {
+        assert_eq!(set("zyxa").len(), 4);
+        assert!(set("abc").contains('b') && !set("abc").contains('d'));
+        assert!(!set("abc").contains('A'));
+        assert_eq!(AnswerSet::ALL, set("abcdefghijklmnopqrstuvwxyz"));
+    }
+
+    #[test]
+    fn rejects_other_characters() {
+        for &(text, column) in [("abc ", 4), ("abA", 3), ("ab\na1", 2), ("é", 1)].iter() {
+            let error = aoc::nom::report(text, &parse_group(text).unwrap_err());
+            assert_eq!(
+                error.message, "expected question (a-z) (Satisfy)",
+                "{:?}",
+                text
+            );
+            assert_eq!(error.column, column, "{:?}", text);
+        }
+        let error = aoc::nom::report("ab\na1", &parse_group("ab\na1").unwrap_err());
+        assert_eq!(error.line, 2);
+    }
 }
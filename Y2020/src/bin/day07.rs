@@ -2,145 +2,495 @@ extern crate derive_more;
 
 extern crate petgraph;
 use petgraph::graph::NodeIndex;
-use petgraph::visit;
-use petgraph::Graph;
+use petgraph::visit::EdgeRef;
+use petgraph::{Direction, Graph};
 
+use nom::branch::alt;
 use nom::bytes::complete::tag;
 use nom::character::complete::{alpha1, char, space0, space1};
-use nom::multi::separated_list0;
-use nom::{sequence, IResult};
+use nom::combinator::value;
+use nom::error::context;
+use nom::multi::separated_list1;
+use nom::sequence;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fmt::Display;
 use std::path::Path;
 
 use advent_of_code_rust_2020 as aoc;
+use aoc::graph::GraphFormat;
+use aoc::io::Input;
+use aoc::nom::ParseResult;
 
 // Type Declarations //
 
-type BagColor = String;
+type BagColor<'a> = &'a str;
 #[derive(Debug, Clone)]
-struct Rule {
-    bag: BagColor,
-    containments: Vec<(BagColor, i32)>,
+struct Rule<'a> {
+    bag: BagColor<'a>,
+    containments: Vec<(BagColor<'a>, u32)>,
 }
 
-type BagGraph = Graph<BagColor, i32>;
+// Edges go from the outer bag to the bags it contains, weighted by their number
+type BagGraph<'a> = Graph<BagColor<'a>, u32>;
 
-// Part 1 //
-
-fn solve1(rules: &Vec<Rule>) -> i32 {
-    let mut graph = rules_to_graph(rules);
-    let start_index = shiny_gold_index(&graph);
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RuleError {
+    // A color without a rule, either queried or inside another bag
+    UndefinedColor(String),
+    DuplicateRule(String),
+    // Colors which contain each other, the first and the last being the same
+    Cycle(Vec<String>),
+    // Bags inside this color, which are too many to count in a u64
+    Overflow(String),
+}
 
-    // Do DFS with reversed edges,
-    // then the footprint will be the answer
-    graph.reverse();
-    let mut count = 0;
-    let mut dfs = visit::Dfs::new(&graph, start_index);
-    dfs.next(&graph); // Skip "shiny gold" itself
-    while let Some(_) = dfs.next(&graph) {
-        count = count + 1;
+impl Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuleError::UndefinedColor(color) => write!(f, "no rule for {:?} bags", color),
+            RuleError::DuplicateRule(color) => write!(f, "more than one rule for {:?} bags", color),
+            RuleError::Cycle(colors) => {
+                write!(f, "bags contain each other: {}", colors.join(" -> "))
+            }
+            RuleError::Overflow(color) => {
+                write!(f, "too many bags inside {:?} bags to count", color)
+            }
+        }
     }
-    count
 }
 
-fn rules_to_graph(rules: &Vec<Rule>) -> BagGraph {
-    let mut graph = BagGraph::new();
-    let mut node_map = HashMap::<&str, NodeIndex>::new();
-    rules.into_iter().for_each(|rule| {
-        let start = rule.bag.as_str();
-        // Try create start node
-        node_map
-            .entry(start)
-            .or_insert(graph.add_node(String::from(start)));
-        (&rule.containments).into_iter().for_each(|(end, weight)| {
-            let end = end.as_str();
-            // Try create end node
-            node_map
-                .entry(end)
-                .or_insert(graph.add_node(String::from(end)));
-            // Create edge
-            graph.add_edge(node_map[start], node_map[end], *weight);
-        })
-    });
-    graph
+// Bags directly inside each other, outermost first
+type BagPath = Vec<NodeIndex>;
+
+// Queries over the containment graph of the rules
+struct BagRules<'a> {
+    graph: BagGraph<'a>,
+    nodes: HashMap<BagColor<'a>, NodeIndex>,
 }
 
-fn shiny_gold_index(graph: &BagGraph) -> NodeIndex {
-    graph
-        .node_indices()
-        .find(|i| graph[*i] == "shiny gold")
-        .unwrap()
+impl<'a> BagRules<'a> {
+    // Fails if a color has two rules, a contained color has none, or bags contain each other
+    fn new(rules: &[Rule<'a>]) -> Result<Self, RuleError> {
+        let mut graph = BagGraph::new();
+        let mut nodes = HashMap::new();
+        for rule in rules.iter() {
+            if nodes.insert(rule.bag, graph.add_node(rule.bag)).is_some() {
+                return Err(RuleError::DuplicateRule(String::from(rule.bag)));
+            }
+        }
+        for rule in rules.iter() {
+            for &(inner, count) in rule.containments.iter() {
+                let &j = nodes
+                    .get(inner)
+                    .ok_or_else(|| RuleError::UndefinedColor(String::from(inner)))?;
+                graph.add_edge(nodes[rule.bag], j, count);
+            }
+        }
+        let rules = BagRules { graph, nodes };
+        // Queries fold bottom-up, so a cycle is found here rather than by the first query
+        let mut memo = HashMap::new();
+        for i in rules.graph.node_indices() {
+            rules.visit(i, &|_, _: &[(u32, &())]| (), &mut memo, &mut vec![])?;
+        }
+        Ok(rules)
+    }
+
+    fn index(&self, color: &str) -> Result<NodeIndex, RuleError> {
+        self.nodes
+            .get(color)
+            .copied()
+            .ok_or_else(|| RuleError::UndefinedColor(String::from(color)))
+    }
+
+    // Colors which eventually contain a 'color' bag
+    fn ancestors(&self, color: &str) -> Result<Vec<BagColor<'a>>, RuleError> {
+        self.reachable(color, Direction::Incoming)
+    }
+
+    // Colors which a 'color' bag eventually contains
+    fn descendants(&self, color: &str) -> Result<Vec<BagColor<'a>>, RuleError> {
+        self.reachable(color, Direction::Outgoing)
+    }
+
+    // Sorted, without 'color' itself
+    fn reachable(&self, color: &str, direction: Direction) -> Result<Vec<BagColor<'a>>, RuleError> {
+        let start = self.index(color)?;
+        let found = aoc::graph::reachable(&self.graph, start, direction);
+        let mut colors: Vec<BagColor> = found
+            .into_iter()
+            .filter(|&i| i != start)
+            .map(|i| self.graph[i])
+            .collect();
+        colors.sort_unstable();
+        Ok(colors)
+    }
+
+    // The rules inside a 'from' bag and containing a 'to' bag, or all of them
+    fn subgraph(&self, from: Option<&str>, to: Option<&str>) -> Result<BagGraph<'a>, RuleError> {
+        let mut keep: HashSet<NodeIndex> = self.graph.node_indices().collect();
+        for &(color, direction) in [(from, Direction::Outgoing), (to, Direction::Incoming)].iter() {
+            if let Some(color) = color {
+                let found = aoc::graph::reachable(&self.graph, self.index(color)?, direction);
+                keep.retain(|i| found.contains(i));
+            }
+        }
+        Ok(aoc::graph::subgraph(&self.graph, &keep))
+    }
+
+    // Total number of bags inside a 'color' bag
+    fn count_inside(&self, color: &str) -> Result<u64, RuleError> {
+        // 'None' once a count overflows
+        let count = self.fold_inside(self.index(color)?, &|_, inner: &[(u32, &Option<u64>)]| {
+            inner.iter().try_fold(0u64, |sum, &(n, &count)| {
+                let bags = count?.checked_add(1)?.checked_mul(u64::from(n))?;
+                sum.checked_add(bags)
+            })
+        })?;
+        count.ok_or_else(|| RuleError::Overflow(String::from(color)))
+    }
+
+    // Longest chain of bags directly inside each other, starting with 'color'
+    fn deepest_path(&self, color: &str) -> Result<Vec<BagColor<'a>>, RuleError> {
+        let path = self.fold_inside(self.index(color)?, &|i, inner: &[(u32, &BagPath)]| {
+            let longest = inner
+                .iter()
+                .map(|&(_, path)| path)
+                .max_by_key(|path| path.len());
+            std::iter::once(i)
+                .chain(longest.into_iter().flatten().copied())
+                .collect()
+        })?;
+        Ok(path.into_iter().map(|i| self.graph[i]).collect())
+    }
+
+    // Chain of bags directly inside each other, starting with 'color',
+    // whose innermost bag is there the most times (the product of the counts along it)
+    fn heaviest_path(&self, color: &str) -> Result<(u64, Vec<BagColor<'a>>), RuleError> {
+        // 'None' once a weight overflows
+        type Weighted = Option<(u64, BagPath)>;
+        let weighted = self.fold_inside(self.index(color)?, &|i, inner: &[(u32, &Weighted)]| {
+            let weights: Option<Vec<(u64, &BagPath)>> = inner
+                .iter()
+                .map(|&(n, weighted)| {
+                    let (weight, path) = weighted.as_ref()?;
+                    Some((weight.checked_mul(u64::from(n))?, path))
+                })
+                .collect();
+            let heaviest = weights?.into_iter().max_by_key(|&(weight, _)| weight);
+            Some(match heaviest {
+                Some((weight, path)) => (
+                    weight,
+                    std::iter::once(i).chain(path.iter().copied()).collect(),
+                ),
+                None => (1, vec![i]),
+            })
+        })?;
+        let (weight, path) = weighted.ok_or_else(|| RuleError::Overflow(String::from(color)))?;
+        Ok((weight, path.into_iter().map(|i| self.graph[i]).collect()))
+    }
+
+    // Bottom-up value of the bag at 'start', combining each bag with the
+    // (count, value) of the bags directly inside it. Every bag is combined once.
+    fn fold_inside<T, F>(&self, start: NodeIndex, combine: &F) -> Result<T, RuleError>
+    where
+        F: Fn(NodeIndex, &[(u32, &T)]) -> T,
+    {
+        let mut memo = HashMap::new();
+        self.visit(start, combine, &mut memo, &mut vec![])?;
+        Ok(memo.remove(&start).expect("Visited bag"))
+    }
+
+    fn visit<T, F>(
+        &self,
+        i: NodeIndex,
+        combine: &F,
+        memo: &mut HashMap<NodeIndex, T>,
+        stack: &mut Vec<NodeIndex>,
+    ) -> Result<(), RuleError>
+    where
+        F: Fn(NodeIndex, &[(u32, &T)]) -> T,
+    {
+        if memo.contains_key(&i) {
+            return Ok(());
+        }
+        if let Some(pos) = stack.iter().position(|&j| j == i) {
+            let cycle = stack[pos..].iter().chain(std::iter::once(&i));
+            return Err(RuleError::Cycle(
+                cycle.map(|&j| String::from(self.graph[j])).collect(),
+            ));
+        }
+        stack.push(i);
+        let edges: Vec<(NodeIndex, u32)> = self
+            .graph
+            .edges(i)
+            .map(|edge| (edge.target(), *edge.weight()))
+            .collect();
+        for &(j, _) in edges.iter() {
+            self.visit(j, combine, memo, stack)?;
+        }
+        stack.pop();
+        let inner: Vec<(u32, &T)> = edges.iter().map(|&(j, n)| (n, &memo[&j])).collect();
+        let value = combine(i, &inner);
+        memo.insert(i, value);
+        Ok(())
+    }
 }
 
-// Part 2 //
+const MY_BAG: &str = "shiny gold";
 
-fn solve2(rules: &Vec<Rule>) -> i32 {
-    let graph = rules_to_graph(rules);
-    let start_index = shiny_gold_index(&graph);
+// Part 1 //
 
-    // "Shiny gold" bag itself is not counted
-    count_bags(&graph, start_index) - 1
+// Colors which can eventually contain my bag
+fn solve1(rules: &BagRules, color: &str) -> Result<usize, RuleError> {
+    rules.ancestors(color).map(|colors| colors.len())
 }
 
-// DFS while counting bags
-fn count_bags(graph: &BagGraph, i: NodeIndex) -> i32 {
-    let self_count = 1;
-    let inner_count: i32 = graph
-        .neighbors(i)
-        .map(|j| {
-            let bag_mult = graph
-                .edge_weight(graph.find_edge(i, j).unwrap())
-                .unwrap_or(&0);
-            (*bag_mult) * count_bags(graph, j)
-        })
-        .sum();
-    self_count + inner_count
+// Part 2 //
+
+// Bags inside my bag
+fn solve2(rules: &BagRules, color: &str) -> Result<u64, RuleError> {
+    rules.count_inside(color)
 }
 
 // I/O //
 
 fn main() {
-    let lines = aoc::io::read_file_line(Path::new("inputs/day07.txt"));
-    let rules: Vec<Rule> = lines
-        .map(|s| aoc::nom::unwrap_parsed(parse_rule(&s)))
-        .collect();
-    println!("{}", solve1(&rules));
-    println!("{}", solve2(&rules));
-}
+    let input = Input::read(&aoc::runner::input_path("inputs/day07.txt"));
+    let rules: Vec<Rule> = input.records(parse_rule);
+    let rules = or_panic(BagRules::new(&rules));
+    // '--color <color>' asks about another bag than "shiny gold"
+    let color = aoc::runner::arg_value("--color").unwrap_or_else(|| String::from(MY_BAG));
+    // Both are answered before printing, so that an error doesn't leave half the output
+    let part1 = or_panic(solve1(&rules, &color));
+    let part2 = or_panic(solve2(&rules, &color));
+    println!("{}", part1);
+    println!("{}", part2);
 
-fn parse_bag(input: &str) -> IResult<&str, BagColor> {
-    let mut bag_color = String::new();
-    let mut i = input;
-    loop {
-        let (ii, word) = sequence::terminated(alpha1, space0)(i)?;
-        i = ii;
-        if word == "bag" || word == "bags" {
-            break;
-        }
-        if bag_color != "" {
-            bag_color = bag_color + " ";
-        }
-        bag_color = bag_color + word;
+    // '--explain' shows the colors inside it, and the deepest and heaviest chains of bags
+    if aoc::runner::has_flag("--explain") {
+        let inside = or_panic(rules.descendants(&color));
+        eprintln!("{} colors inside: {}", inside.len(), inside.join(", "));
+        let deepest = or_panic(rules.deepest_path(&color));
+        eprintln!("deepest ({} bags): {}", deepest.len(), deepest.join(" > "));
+        let (weight, heaviest) = or_panic(rules.heaviest_path(&color));
+        eprintln!(
+            "heaviest ({} innermost bags): {}",
+            weight,
+            heaviest.join(" > ")
+        );
     }
-    Ok((i, bag_color))
+
+    if let Some(path) = aoc::runner::arg_value("--export") {
+        export(&rules, &path);
+    }
+}
+
+// '--export <path>' writes the rules as DOT ('.dot', '.gv') or GraphML ('.graphml'),
+// only those inside '--from <color>' and containing '--to <color>' if given
+fn export(rules: &BagRules, path: &str) {
+    let path = Path::new(path);
+    let format =
+        GraphFormat::from_path(path).expect("Export path should end in .dot, .gv or .graphml");
+    let from = aoc::runner::arg_value("--from");
+    let to = aoc::runner::arg_value("--to");
+    let graph = or_panic(rules.subgraph(from.as_deref(), to.as_deref()));
+    aoc::graph::write_graph(&graph, format, path).expect("Graph export error");
+    eprintln!(
+        "{}: {} colors, {} rules",
+        path.display(),
+        graph.node_count(),
+        graph.edge_count()
+    );
+}
+
+fn or_panic<T>(result: Result<T, RuleError>) -> T {
+    result.unwrap_or_else(|e| panic!("{}", e))
+}
+
+// Borrows words before "bag(s)" as a single slice
+fn parse_bag<'a>(input: &'a str) -> ParseResult<'a, BagColor<'a>> {
+    sequence::terminated(
+        aoc::nom::words_until(&["bag", "bags"]),
+        sequence::pair(alpha1, space0),
+    )(input)
 }
 
-fn parse_contained_bag(input: &str) -> IResult<&str, (BagColor, i32)> {
-    sequence::tuple((aoc::nom::number, space1, parse_bag))(input)
+fn parse_contained_bag<'a>(input: &'a str) -> ParseResult<'a, (BagColor<'a>, u32)> {
+    sequence::tuple((aoc::nom::unsigned, space1, parse_bag))(input)
         .map(|(input, (n, _, bag_color))| (input, (bag_color, n)))
 }
 
-fn parse_rule(input: &str) -> IResult<&str, Rule> {
-    let (input, bag) = parse_bag(input)?;
+fn parse_rule<'a>(input: &'a str) -> ParseResult<'a, Rule<'a>> {
+    let (input, bag) = context("bag", parse_bag)(input)?;
     let (input, _) = sequence::tuple((tag("contain"), space1))(input)?;
-    let (_, contains) =
-        separated_list0(sequence::pair(char(','), space1), parse_contained_bag)(input)?;
+    let (input, contains) = alt((
+        value(vec![], tag("no other bags")),
+        separated_list1(
+            sequence::pair(char(','), space1),
+            context("contained bag", parse_contained_bag),
+        ),
+    ))(input)?;
+    let (input, _) = char('.')(input)?;
     Ok((
-        "", // Ignore remaining inputs
+        input,
         Rule {
-            bag: bag,
+            bag,
             containments: contains,
         },
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    const EXAMPLE: &str = "\
+light red bags contain 1 bright white bag, 2 muted yellow bags.
+dark orange bags contain 3 bright white bags, 4 muted yellow bags.
+bright white bags contain 1 shiny gold bag.
+muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
+shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.
+dark olive bags contain 3 faded blue bags, 4 dotted black bags.
+vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
+faded blue bags contain no other bags.
+dotted black bags contain no other bags.";
+
+    const CHAIN: &str = "\
+shiny gold bags contain 2 dark red bags.
+dark red bags contain 2 dark orange bags.
+dark orange bags contain 2 dark yellow bags.
+dark yellow bags contain 2 dark green bags.
+dark green bags contain 2 dark blue bags.
+dark blue bags contain 2 dark violet bags.
+dark violet bags contain no other bags.";
+
+    fn parse(text: &str) -> Vec<Rule<'_>> {
+        text.lines()
+            .map(|line| aoc::nom::unwrap_parsed(line, parse_rule(line)))
+            .collect()
+    }
+
+    fn bag_rules(text: &str) -> Result<BagRules<'_>, RuleError> {
+        BagRules::new(&parse(text))
+    }
+
+    #[test]
+    fn queries() {
+        let rules = bag_rules(EXAMPLE).unwrap();
+        assert_eq!(solve1(&rules, MY_BAG), Ok(4));
+        assert_eq!(solve2(&rules, MY_BAG), Ok(32));
+        assert_eq!(
+            rules.descendants(MY_BAG),
+            Ok(vec![
+                "dark olive",
+                "dotted black",
+                "faded blue",
+                "vibrant plum"
+            ])
+        );
+        let rules = bag_rules(CHAIN).unwrap();
+        assert_eq!(solve2(&rules, MY_BAG), Ok(126));
+    }
+
+    #[test]
+    fn paths() {
+        let rules = bag_rules(EXAMPLE).unwrap();
+        assert_eq!(
+            rules.heaviest_path(MY_BAG),
+            Ok((12, vec!["shiny gold", "vibrant plum", "dotted black"]))
+        );
+        assert_eq!(rules.deepest_path("faded blue"), Ok(vec!["faded blue"]));
+        let rules = bag_rules(CHAIN).unwrap();
+        let chain = vec![
+            "shiny gold",
+            "dark red",
+            "dark orange",
+            "dark yellow",
+            "dark green",
+            "dark blue",
+            "dark violet",
+        ];
+        assert_eq!(rules.deepest_path(MY_BAG), Ok(chain.clone()));
+        assert_eq!(rules.heaviest_path(MY_BAG), Ok((64, chain)));
+    }
+
+    #[test]
+    fn shared_bags_are_combined_once() {
+        // "faded blue" and "dotted black" are inside both "dark olive" and "vibrant plum"
+        let rules = bag_rules(EXAMPLE).unwrap();
+        let calls = Cell::new(0);
+        let start = rules.index(MY_BAG).unwrap();
+        let count = rules.fold_inside(start, &|_, inner: &[(u32, &u64)]| {
+            calls.set(calls.get() + 1);
+            inner.iter().map(|&(n, &c)| u64::from(n) * (1 + c)).sum()
+        });
+        assert_eq!(count, Ok(32));
+        assert_eq!(calls.get(), 5);
+    }
+
+    #[test]
+    fn errors() {
+        let cycle = "\
+shiny gold bags contain 1 dark red bag.
+dark red bags contain 1 light blue bag, 1 faded blue bag.
+light blue bags contain 2 dark red bags.
+faded blue bags contain no other bags.";
+        let colors = vec!["dark red", "light blue", "dark red"];
+        let colors = colors.into_iter().map(String::from).collect();
+        assert_eq!(bag_rules(cycle).err(), Some(RuleError::Cycle(colors)));
+
+        let undefined = "shiny gold bags contain 1 dark red bag.";
+        let error = RuleError::UndefinedColor(String::from("dark red"));
+        assert_eq!(bag_rules(undefined).err(), Some(error));
+        let rules = bag_rules(EXAMPLE).unwrap();
+        let error = RuleError::UndefinedColor(String::from("plaid"));
+        assert_eq!(rules.count_inside("plaid"), Err(error));
+
+        let duplicate = "\
+shiny gold bags contain no other bags.
+shiny gold bags contain 1 dark red bag.
+dark red bags contain no other bags.";
+        let error = RuleError::DuplicateRule(String::from("shiny gold"));
+        assert_eq!(bag_rules(duplicate).err(), Some(error));
+    }
+
+    #[test]
+    fn overflow() {
+        // "a a" bags hold 1000 "b b" bags, each holding 1000 "c c" bags, and so on
+        let nested = |depth: u8| -> String {
+            let color = |i: u8| format!("{0} {0}", (b'a' + i) as char);
+            let mut lines: Vec<String> = (0..depth)
+                .map(|i| format!("{} bags contain 1000 {} bags.", color(i), color(i + 1)))
+                .collect();
+            lines.push(format!("{} bags contain no other bags.", color(depth)));
+            lines.join("\n")
+        };
+        let text = nested(6);
+        let rules = bag_rules(&text).unwrap();
+        assert_eq!(rules.count_inside("a a"), Ok(1_001_001_001_001_001_000));
+        assert_eq!(
+            rules.heaviest_path("a a").unwrap().0,
+            1_000_000_000_000_000_000
+        );
+
+        let text = nested(8);
+        let rules = bag_rules(&text).unwrap();
+        let error = RuleError::Overflow(String::from("a a"));
+        assert_eq!(rules.count_inside("a a"), Err(error.clone()));
+        assert_eq!(rules.heaviest_path("a a").err(), Some(error));
+        assert_eq!(rules.deepest_path("a a").unwrap().len(), 9);
+        // Bags further in are still counted
+        assert_eq!(rules.count_inside("c c"), Ok(1_001_001_001_001_001_000));
+    }
+
+    #[test]
+    fn counts_are_unsigned() {
+        assert!(parse_rule("shiny gold bags contain -1 dark red bag.").is_err());
+        let rule = parse_rule("shiny gold bags contain 1 dark red bag, 22 light blue bags.");
+        let (_, rule) = rule.unwrap();
+        assert_eq!(rule.containments, vec![("dark red", 1), ("light blue", 22)]);
+    }
+}
@@ -1,6 +1,5 @@
 extern crate derive_more;
 
-use std::path::Path;
 use std::str::FromStr;
 
 use advent_of_code_rust_2020 as aoc;
@@ -139,7 +138,7 @@ fn check_terminated(program: &Program, st: &ProgramState) -> bool {
 // I/O //
 
 fn main() {
-    let program: Program = aoc::io::read_file_vec(Path::new("inputs/day08.txt"));
+    let program: Program = aoc::io::read_file_vec(&aoc::runner::input_path("inputs/day08.txt"));
     println!("{}", solve1(&program));
     println!("{}", solve2(program));
 }
@@ -1,7 +1,5 @@
 extern crate derive_more;
 
-use std::path::Path;
-
 use advent_of_code_rust_2020 as aoc;
 
 // Type Declarations //
@@ -60,7 +58,7 @@ fn find_windowed_sum(data: &[Number], target: Number, window_size: usize) -> Opt
 // I/O //
 
 fn main() {
-    let data: Vec<Number> = aoc::io::read_file_vec(Path::new("inputs/day09.txt"));
+    let data: Vec<Number> = aoc::io::read_file_vec(&aoc::runner::input_path("inputs/day09.txt"));
     let answer_1 = solve1(&data);
     println!("{}", answer_1);
     println!("{}", solve2(&data, answer_1));
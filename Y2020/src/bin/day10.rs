@@ -1,8 +1,6 @@
 extern crate derive_more;
 use derive_more::{Add, FromStr, Sub};
 
-use std::path::Path;
-
 use advent_of_code_rust_2020 as aoc;
 
 // Type Declarations //
@@ -90,7 +88,7 @@ fn count_arrangements(jolts: &[Jolt]) -> u64 {
 // I/O //
 
 fn main() {
-    let jolts: Vec<Jolt> = aoc::io::read_file_vec(Path::new("inputs/day10.txt"));
+    let jolts: Vec<Jolt> = aoc::io::read_file_vec(&aoc::runner::input_path("inputs/day10.txt"));
     println!("{}", solve1(&jolts));
     println!("{}", solve2(&jolts));
 }
@@ -5,7 +5,12 @@ use std::fmt::Display;
 use std::path::Path;
 
 use advent_of_code_rust_2020 as aoc;
-use aoc::grid::Grid;
+use aoc::automaton::{Automaton, Rule};
+use aoc::grid::{Grid, Pos};
+use aoc::image::{FrameWriter, ImageFormat, Rgb};
+#[cfg(feature = "parallel")]
+use aoc::runner::report_timing;
+use aoc::runner::timed;
 
 // Type Declarations //
 
@@ -43,42 +48,71 @@ impl Display for Tile {
     }
 }
 
+fn tile_color(tile: &Tile) -> Rgb {
+    match tile {
+        Tile::Floor => Rgb(40, 40, 40),
+        Tile::Empty => Rgb(90, 160, 90),
+        Tile::Occupied => Rgb(220, 80, 60),
+    }
+}
+
 // Part 1 //
 
-fn solve1(grid: &Grid<Tile>) -> i32 {
-    let mut grid: Grid<Tile> = Grid::from(grid.vec.clone());
-    loop {
-        let new_grid = step(&grid);
-        if grid.vec == new_grid.vec {
-            break;
-        }
-        grid = new_grid
-    }
+type Step<R> = fn(&mut Automaton<Tile, R>) -> Vec<Pos>;
+
+fn solve1(
+    grid: &Grid<Tile>,
+    step: Step<Adjacent>,
+    mut on_generation: impl FnMut(&Grid<Tile>),
+) -> i32 {
+    let mut automaton = Automaton::new(grid.clone(), Adjacent);
+    automaton.run_until_stable_by(step, |grid, _| on_generation(grid));
+    count_occupied(automaton.grid())
+}
+
+fn count_occupied(grid: &Grid<Tile>) -> i32 {
     grid.vec
         .iter()
         .map(|l| l.iter().filter(|&x| *x == Tile::Occupied).count() as i32)
         .sum()
 }
 
-fn step(grid: &Grid<Tile>) -> Grid<Tile> {
-    let (row_len, col_len) = grid.size();
-    let next_grid: Vec<Vec<Tile>> = (0..row_len)
-        .map(|i| (0..col_len).map(|j| next_tile(grid, i, j)).collect())
-        .collect();
-    Grid::from(next_grid)
+const DELTAS: [(i32, i32); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+// Seats look at 8 adjacent tiles
+struct Adjacent;
+impl Rule<Tile> for Adjacent {
+    fn next(&self, grid: &Grid<Tile>, pos: Pos) -> Tile {
+        next_tile(grid[pos], adjacent_occupied(grid, pos), 4)
+    }
+    fn dependents(&self, grid: &Grid<Tile>, pos: Pos) -> Vec<Pos> {
+        DELTAS
+            .iter()
+            .filter_map(|&delta| grid.offset(pos, delta))
+            .collect()
+    }
 }
 
-fn next_tile(grid: &Grid<Tile>, i: usize, j: usize) -> Tile {
-    match grid.vec[i][j] {
+fn next_tile(tile: Tile, occupied: usize, tolerance: usize) -> Tile {
+    match tile {
         Tile::Empty => {
-            if adjacent_occupied(grid, i, j) == 0 {
+            if occupied == 0 {
                 Tile::Occupied
             } else {
                 Tile::Empty
             }
         }
         Tile::Occupied => {
-            if adjacent_occupied(grid, i, j) >= 4 {
+            if occupied >= tolerance {
                 Tile::Empty
             } else {
                 Tile::Occupied
@@ -88,124 +122,101 @@ fn next_tile(grid: &Grid<Tile>, i: usize, j: usize) -> Tile {
     }
 }
 
-fn adjacent_occupied(grid: &Grid<Tile>, i: usize, j: usize) -> usize {
-    let deltas = [
-        (-1, -1),
-        (-1, 0),
-        (-1, 1),
-        (0, -1),
-        (0, 1),
-        (1, -1),
-        (1, 0),
-        (1, 1),
-    ];
-    deltas
+fn adjacent_occupied(grid: &Grid<Tile>, pos: Pos) -> usize {
+    DELTAS
         .iter()
-        .map(|&(dx, dy)| {
-            if let Some(tile) = get_tile(grid, i, j, dx, dy) {
-                if tile == Tile::Occupied {
-                    1
-                } else {
-                    0
-                }
-            } else {
-                0
-            }
-        })
-        .sum()
-}
-
-fn get_tile(grid: &Grid<Tile>, i: usize, j: usize, dx: i32, dy: i32) -> Option<Tile> {
-    let x = (i as i32) + dx;
-    let y = (j as i32) + dy;
-    if (x < 0) || (y < 0) {
-        return None;
-    };
-    Some(*grid.vec.get(x as usize)?.get(y as usize)?)
+        .filter_map(|&delta| grid.offset(pos, delta))
+        .filter(|&adj| grid[adj] == Tile::Occupied)
+        .count()
 }
 
 // Part 2 //
 
-fn solve2(grid: &Grid<Tile>) -> i32 {
-    let mut grid: Grid<Tile> = Grid::from(grid.vec.clone());
-    loop {
-        let new_grid = step2(&grid);
-        if grid.vec == new_grid.vec {
-            break;
-        }
-        grid = new_grid
-    }
-    grid.vec
-        .iter()
-        .map(|l| l.iter().filter(|&x| *x == Tile::Occupied).count() as i32)
-        .sum()
-}
-
-fn step2(grid: &Grid<Tile>) -> Grid<Tile> {
-    let (row_len, col_len) = grid.size();
-    let next_grid: Vec<Vec<Tile>> = (0..row_len)
-        .map(|i| (0..col_len).map(|j| next_tile2(grid, i, j)).collect())
-        .collect();
-    Grid::from(next_grid)
+fn solve2(
+    grid: &Grid<Tile>,
+    step: Step<Visible>,
+    mut on_generation: impl FnMut(&Grid<Tile>),
+) -> i32 {
+    let mut automaton = Automaton::new(grid.clone(), Visible);
+    automaton.run_until_stable_by(step, |grid, _| on_generation(grid));
+    count_occupied(automaton.grid())
 }
 
-fn next_tile2(grid: &Grid<Tile>, i: usize, j: usize) -> Tile {
-    match grid.vec[i][j] {
-        Tile::Empty => {
-            if adjacent_occupied2(grid, i, j) == 0 {
-                Tile::Occupied
-            } else {
-                Tile::Empty
-            }
-        }
-        Tile::Occupied => {
-            if adjacent_occupied2(grid, i, j) >= 5 {
-                Tile::Empty
-            } else {
-                Tile::Occupied
-            }
-        }
-        Tile::Floor => Tile::Floor,
+// Seats look at the first seat in each of 8 directions
+// Floor never changes, so 'visible_seats' is the same in every generation
+struct Visible;
+impl Rule<Tile> for Visible {
+    fn next(&self, grid: &Grid<Tile>, pos: Pos) -> Tile {
+        let occupied = visible_seats(grid, pos)
+            .into_iter()
+            .filter(|&seat| grid[seat] == Tile::Occupied)
+            .count();
+        next_tile(grid[pos], occupied, 5)
+    }
+    fn dependents(&self, grid: &Grid<Tile>, pos: Pos) -> Vec<Pos> {
+        // Visibility is symmetric
+        visible_seats(grid, pos)
     }
 }
 
-fn adjacent_occupied2(grid: &Grid<Tile>, i: usize, j: usize) -> usize {
-    let deltas = [
-        (-1, -1),
-        (-1, 0),
-        (-1, 1),
-        (0, -1),
-        (0, 1),
-        (1, -1),
-        (1, 0),
-        (1, 1),
-    ];
-    deltas
+fn visible_seats(grid: &Grid<Tile>, pos: Pos) -> Vec<Pos> {
+    DELTAS
         .iter()
-        .map(|&(dx, dy)| {
-            let (mut i, mut j) = (i, j);
+        .filter_map(|&delta| {
+            let mut pos = pos;
             loop {
-                if let Some(tile) = get_tile(grid, i, j, dx, dy) {
-                    match tile {
-                        Tile::Occupied => break 1,
-                        Tile::Empty => break 0,
-                        Tile::Floor => {
-                            i = ((i as i32) + dx) as usize;
-                            j = ((j as i32) + dy) as usize;
-                        }
-                    }
-                } else {
-                    break 0;
+                pos = grid.offset(pos, delta)?;
+                if grid[pos] != Tile::Floor {
+                    break Some(pos);
                 }
             }
         })
-        .sum()
+        .collect()
 }
 
 // I/O //
 
 fn main() {
-    let grid: Grid<Tile> = aoc::io::read_file_grid(Path::new("inputs/day11.txt"));
-    println!("{}", solve1(&grid));
-    println!("{}", solve2(&grid));
+    let grid: Grid<Tile> = aoc::io::read_file_grid(&aoc::runner::input_path("inputs/day11.txt"));
+    // '--frames <dir>' dumps every generation as an image
+    let frame_dir: Option<String> = aoc::runner::arg_value("--frames");
+    let answer1 = timed("part 1", || {
+        solve1(&grid, Automaton::step, frame_recorder(&frame_dir, "part1"))
+    });
+    println!("{}", answer1);
+    let answer2 = timed("part 2", || {
+        solve2(&grid, Automaton::step, frame_recorder(&frame_dir, "part2"))
+    });
+    println!("{}", answer2);
+
+    #[cfg(feature = "parallel")]
+    compare_stepping(&grid);
+}
+
+// Full-grid stepping, sequential vs. rayon
+#[cfg(feature = "parallel")]
+fn compare_stepping(grid: &Grid<Tile>) {
+    use aoc::runner::measure;
+    let (seq1, seq1_time) = measure(|| solve1(grid, Automaton::step_full, |_| ()));
+    let (par1, par1_time) = measure(|| solve1(grid, Automaton::step_parallel, |_| ()));
+    let (seq2, seq2_time) = measure(|| solve2(grid, Automaton::step_full, |_| ()));
+    let (par2, par2_time) = measure(|| solve2(grid, Automaton::step_parallel, |_| ()));
+    assert_eq!(seq1, par1, "Parallel stepping should give the same answer");
+    assert_eq!(seq2, par2, "Parallel stepping should give the same answer");
+    report_timing("part 1, sequential", seq1_time);
+    report_timing("part 1, parallel", par1_time);
+    report_timing("part 2, sequential", seq2_time);
+    report_timing("part 2, parallel", par2_time);
+}
+
+fn frame_recorder(frame_dir: &Option<String>, part: &str) -> impl FnMut(&Grid<Tile>) {
+    let mut writer = frame_dir.as_ref().map(|dir| {
+        FrameWriter::new(&Path::new(dir).join(part), ImageFormat::Png, 4)
+            .expect("Frame directory error")
+    });
+    move |grid| {
+        if let Some(writer) = writer.as_mut() {
+            writer.write(grid, tile_color).expect("Frame write error");
+        }
+    }
 }
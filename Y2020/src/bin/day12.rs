@@ -1,7 +1,6 @@
 #![feature(bindings_after_at)]
 extern crate derive_more;
 
-use std::path::Path;
 use std::str::FromStr;
 
 use advent_of_code_rust_2020 as aoc;
@@ -172,7 +171,8 @@ fn rotate_wp_left(wp @ (x, y): (i32, i32), angle: i32) -> (i32, i32) {
 // I/O //
 
 fn main() {
-    let instructions: Vec<Instr> = aoc::io::read_file_vec(Path::new("inputs/day12.txt"));
+    let instructions: Vec<Instr> =
+        aoc::io::read_file_vec(&aoc::runner::input_path("inputs/day12.txt"));
     println!("{}", solve1(&instructions));
     println!("{}", solve2(&instructions));
 }
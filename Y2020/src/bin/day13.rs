@@ -3,15 +3,13 @@ extern crate derive_more;
 extern crate itertools;
 
 extern crate nom;
-use nom::branch::alt;
-use nom::character::complete::char;
+use nom::character::complete::line_ending;
 use nom::combinator::map;
-use nom::multi::separated_list1;
-use nom::IResult;
-
-use std::path::Path;
+use nom::error::context;
+use nom::sequence;
 
 use advent_of_code_rust_2020 as aoc;
+use aoc::nom::ParseResult;
 
 // Type Declarations //
 
@@ -119,18 +117,24 @@ fn merge_schedule(c1: &ContestSchedule, c2: &ContestSchedule) -> ContestSchedule
 // I/O //
 
 fn main() {
-    let input: Vec<String> = aoc::io::read_file_vec(Path::new("inputs/day13.txt"));
-    let arrive_time: i32 = input[0].parse().expect("Number expected");
-    let schedules: Vec<Schedule> = aoc::nom::unwrap_parsed(parse_schedules(&input[1]));
-    println!("{}", solve1(&schedules, arrive_time));
-    println!("{}", solve2(&schedules));
+    // The whole input is a single section
+    let notes =
+        aoc::io::read_section_records(&aoc::runner::input_path("inputs/day13.txt"), parse_notes);
+    let (arrive_time, schedules) = &notes[0];
+    println!("{}", solve1(schedules, *arrive_time));
+    println!("{}", solve2(schedules));
+}
+
+fn parse_notes(input: &str) -> ParseResult<'_, (i32, Vec<Schedule>)> {
+    sequence::separated_pair(
+        context("arrival time", aoc::nom::number),
+        line_ending,
+        context("schedules", parse_schedules),
+    )(input)
 }
 
-fn parse_schedules(input: &str) -> IResult<&str, Vec<Schedule>> {
-    separated_list1(char(','), |i| -> IResult<&str, Schedule> {
-        alt((
-            map(char('x'), |_| Schedule(None)),
-            map(aoc::nom::number, |n| Schedule(Some(n))),
-        ))(i)
+fn parse_schedules(input: &str) -> ParseResult<'_, Vec<Schedule>> {
+    map(aoc::nom::sparse_list('x', aoc::nom::number), |ids| {
+        ids.into_iter().map(Schedule).collect()
     })(input)
 }
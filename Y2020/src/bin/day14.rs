@@ -2,7 +2,6 @@ extern crate derive_more;
 
 use std::collections::HashMap;
 use std::iter::FromIterator;
-use std::path::Path;
 
 use advent_of_code_rust_2020 as aoc;
 
@@ -218,7 +217,7 @@ impl Iterator for MaskIter {
 // I/O //
 
 fn main() {
-    let input = aoc::io::read_file_line(Path::new("inputs/day14.txt"));
+    let input = aoc::io::read_file_line(&aoc::runner::input_path("inputs/day14.txt"));
     let program: Vec<ProgramLine> = input.map(parse_programline).collect();
     println!("{}", solve1(&program));
     println!("{}", solve2(&program));
@@ -1,7 +1,5 @@
 extern crate derive_more;
 
-use std::path::Path;
-
 use advent_of_code_rust_2020 as aoc;
 
 // Type Declarations //
@@ -72,7 +70,7 @@ fn solve2(starting_numbers: &[i32]) -> i32 {
 // I/O //
 
 fn main() {
-    let input: String = aoc::io::read_file_line(Path::new("inputs/day15.txt"))
+    let input: String = aoc::io::read_file_line(&aoc::runner::input_path("inputs/day15.txt"))
         .next()
         .unwrap();
     let starting_numbers: Vec<i32> = input.split(',').map(|x| x.parse().unwrap()).collect();
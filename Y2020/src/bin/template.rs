@@ -1,7 +1,5 @@
 extern crate derive_more;
 
-use std::path::Path;
-
 use advent_of_code_rust_2020 as aoc;
 
 // Type Declarations //
@@ -21,7 +19,7 @@ fn solve2() -> {
 // I/O //
 
 fn main() {
-    let input: Vec<_> = aoc::io::read_file_vec(Path::new("inputs/dayXX.txt"));
+    let input: Vec<_> = aoc::io::read_file_vec(&aoc::runner::input_path("inputs/dayXX.txt"));
     println!("{}", solve1());
     // println!("{}", solve2());
 }
@@ -0,0 +1,362 @@
+use std::fmt;
+use std::fmt::Display;
+
+use crate::grid::{Grid, Pos};
+
+const WORD_BITS: usize = 64;
+
+// Two-valued grid with each row packed into words.
+// Column j is bit (j % 64) of word (j / 64); bits past the last column are kept zero.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitGrid {
+    rows: Vec<Vec<u64>>,
+    row_len: usize,
+    col_len: usize,
+}
+
+impl BitGrid {
+    pub fn new(row_len: usize, col_len: usize) -> Self {
+        BitGrid {
+            rows: vec![vec![0; word_count(col_len)]; row_len],
+            row_len,
+            col_len,
+        }
+    }
+
+    pub fn from_grid<T, P>(grid: &Grid<T>, predicate: P) -> Self
+    where
+        P: Fn(&T) -> bool,
+    {
+        let (row_len, col_len) = grid.size();
+        let mut bits = BitGrid::new(row_len, col_len);
+        grid.positions()
+            .filter(|&pos| predicate(&grid[pos]))
+            .for_each(|pos| bits.set(pos, true));
+        bits
+    }
+
+    pub fn to_grid<T, F>(&self, f: F) -> Grid<T>
+    where
+        F: Fn(bool) -> T,
+    {
+        let vec: Vec<Vec<T>> = (0..self.row_len)
+            .map(|i| (0..self.col_len).map(|j| f(self.get((i, j)))).collect())
+            .collect();
+        Grid::from(vec)
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        (self.row_len, self.col_len)
+    }
+
+    pub fn get(&self, (i, j): Pos) -> bool {
+        assert!(j < self.col_len, "Column out of range");
+        self.rows[i][j / WORD_BITS] >> (j % WORD_BITS) & 1 == 1
+    }
+
+    pub fn set(&mut self, (i, j): Pos, value: bool) {
+        assert!(j < self.col_len, "Column out of range");
+        let word = &mut self.rows[i][j / WORD_BITS];
+        let bit = 1 << (j % WORD_BITS);
+        if value {
+            *word |= bit;
+        } else {
+            *word &= !bit;
+        }
+    }
+
+    pub fn count_ones(&self) -> usize {
+        (0..self.row_len).map(|i| self.row_count_ones(i)).sum()
+    }
+
+    pub fn row_count_ones(&self, i: usize) -> usize {
+        self.rows[i].iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    // Moves every cell of row 'i' by 'n' columns (positive: to the right).
+    // Cells moved past either end are dropped.
+    pub fn shift_row(&mut self, i: usize, n: isize) {
+        self.rows[i] = shift_words(&self.rows[i], n, self.col_len);
+    }
+
+    // Same as 'shift_row', but cells moved past one end come back from the other
+    pub fn rotate_row(&mut self, i: usize, n: isize) {
+        if self.col_len == 0 {
+            return;
+        }
+        let n = n.rem_euclid(self.col_len as isize);
+        let wrapped = n - self.col_len as isize;
+        let row = &self.rows[i];
+        self.rows[i] = shift_words(row, n, self.col_len)
+            .into_iter()
+            .zip(shift_words(row, wrapped, self.col_len))
+            .map(|(a, b)| a | b)
+            .collect();
+    }
+
+    // Number of set cells among the 8 neighbors of each cell
+    pub fn neighbor_counts(&self) -> Grid<u8> {
+        let planes = self.neighbor_planes();
+        let vec: Vec<Vec<u8>> = planes
+            .iter()
+            .map(|row| {
+                (0..self.col_len)
+                    .map(|j| {
+                        let (k, b) = (j / WORD_BITS, j % WORD_BITS);
+                        (0..4).fold(0, |count, p| count | ((row[k][p] >> b & 1) as u8) << p)
+                    })
+                    .collect()
+            })
+            .collect();
+        Grid::from(vec)
+    }
+
+    // Cells with at least 'k' set neighbors
+    pub fn neighbors_at_least(&self, k: u8) -> BitGrid {
+        let rows = self
+            .neighbor_planes()
+            .iter()
+            .map(|row| {
+                let words: Vec<u64> = row.iter().map(|planes| at_least(planes, k)).collect();
+                mask_tail(words, self.col_len)
+            })
+            .collect();
+        BitGrid {
+            rows,
+            row_len: self.row_len,
+            col_len: self.col_len,
+        }
+    }
+
+    // Bit-sliced neighbor counts: bit p of a count is stored in plane p,
+    // so 64 cells are summed with a handful of word operations
+    fn neighbor_planes(&self) -> Vec<Vec<[u64; 4]>> {
+        let words = word_count(self.col_len);
+        let zero_row = vec![0; words];
+        // Each row as is, with left neighbors moved in, and with right neighbors moved in
+        let shifted: Vec<[Vec<u64>; 3]> = self
+            .rows
+            .iter()
+            .map(|row| {
+                [
+                    row.clone(),
+                    shift_words(row, 1, self.col_len),
+                    shift_words(row, -1, self.col_len),
+                ]
+            })
+            .collect();
+        let blank = [zero_row.clone(), zero_row.clone(), zero_row];
+
+        (0..self.row_len)
+            .map(|i| {
+                let above = if i > 0 { &shifted[i - 1] } else { &blank };
+                let below = shifted.get(i + 1).unwrap_or(&blank);
+                let same = &shifted[i];
+                (0..words)
+                    .map(|k| {
+                        let inputs = [
+                            above[0][k],
+                            above[1][k],
+                            above[2][k],
+                            same[1][k],
+                            same[2][k],
+                            below[0][k],
+                            below[1][k],
+                            below[2][k],
+                        ];
+                        inputs.iter().fold([0; 4], |planes, &x| add_bits(planes, x))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl Display for BitGrid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for i in 0..self.row_len {
+            for j in 0..self.col_len {
+                write!(f, "{}", if self.get((i, j)) { '#' } else { '.' })?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+fn word_count(col_len: usize) -> usize {
+    col_len.div_ceil(WORD_BITS)
+}
+
+// Clears bits past the last column
+fn mask_tail(mut words: Vec<u64>, col_len: usize) -> Vec<u64> {
+    let tail = col_len % WORD_BITS;
+    if let (Some(last), true) = (words.last_mut(), tail != 0) {
+        *last &= (1 << tail) - 1;
+    }
+    words
+}
+
+// Positive 'n' moves bits toward higher columns
+fn shift_words(words: &[u64], n: isize, col_len: usize) -> Vec<u64> {
+    let len = words.len() as isize;
+    let (w, b) = (
+        n.div_euclid(WORD_BITS as isize),
+        n.rem_euclid(WORD_BITS as isize) as u32,
+    );
+    let word_at = |k: isize| {
+        if 0 <= k && k < len {
+            words[k as usize]
+        } else {
+            0
+        }
+    };
+    let shifted = (0..len)
+        .map(|k| {
+            let low = word_at(k - w) << b;
+            let carry = if b == 0 {
+                0
+            } else {
+                word_at(k - w - 1) >> (WORD_BITS as u32 - b)
+            };
+            low | carry
+        })
+        .collect();
+    mask_tail(shifted, col_len)
+}
+
+// Adds a 1-bit value to every 4-bit counter in 'planes' (ripple-carry)
+fn add_bits(planes: [u64; 4], x: u64) -> [u64; 4] {
+    let mut carry = x;
+    let mut out = planes;
+    out.iter_mut().for_each(|plane| {
+        let sum = *plane ^ carry;
+        carry &= *plane;
+        *plane = sum;
+    });
+    out
+}
+
+// Mask of counters which are >= k, compared from the most significant bit
+fn at_least(planes: &[u64; 4], k: u8) -> u64 {
+    if k > 15 {
+        return 0;
+    }
+    let (greater, equal) = (0..4).rev().fold((0, !0u64), |(greater, equal), p| {
+        if k >> p & 1 == 0 {
+            (greater | (equal & planes[p]), equal & !planes[p])
+        } else {
+            (greater, equal & planes[p])
+        }
+    });
+    greater | equal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Widths around word boundaries
+    const WIDTHS: [usize; 6] = [1, 5, 63, 64, 65, 130];
+
+    // Deterministic pattern of about half set cells
+    fn pattern(row_len: usize, col_len: usize, seed: u64) -> BitGrid {
+        let mut state = seed;
+        let mut grid = BitGrid::new(row_len, col_len);
+        for i in 0..row_len {
+            for j in 0..col_len {
+                state = state
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1);
+                grid.set((i, j), state >> 63 == 1);
+            }
+        }
+        grid
+    }
+
+    fn row(grid: &BitGrid, i: usize) -> Vec<bool> {
+        (0..grid.col_len).map(|j| grid.get((i, j))).collect()
+    }
+
+    #[test]
+    fn set_and_get() {
+        let mut grid = BitGrid::new(2, 70);
+        grid.set((1, 0), true);
+        grid.set((1, 63), true);
+        grid.set((1, 64), true);
+        grid.set((1, 69), true);
+        assert_eq!(grid.count_ones(), 4);
+        grid.set((1, 64), false);
+        assert_eq!(grid.row_count_ones(1), 3);
+        assert!(grid.get((1, 63)) && !grid.get((1, 64)) && grid.get((1, 69)));
+        let from_grid = BitGrid::from_grid(&grid.to_grid(|x| x), |&x| x);
+        assert_eq!(from_grid, grid);
+    }
+
+    #[test]
+    fn shifts_across_words() {
+        for &col_len in WIDTHS.iter() {
+            let grid = pattern(1, col_len, col_len as u64);
+            let cells = row(&grid, 0);
+            for &n in [-131, -65, -64, -63, -1, 0, 1, 63, 64, 65, 131].iter() {
+                let mut shifted = grid.clone();
+                shifted.shift_row(0, n);
+                let expected: Vec<bool> = (0..col_len as isize)
+                    .map(|j| {
+                        let from = j - n;
+                        (0..col_len as isize).contains(&from) && cells[from as usize]
+                    })
+                    .collect();
+                assert_eq!(row(&shifted, 0), expected, "{} columns by {}", col_len, n);
+                // Nothing is left past the last column
+                assert_eq!(shifted.rows[0], mask_tail(shifted.rows[0].clone(), col_len));
+
+                let mut rotated = grid.clone();
+                rotated.rotate_row(0, n);
+                let expected: Vec<bool> = (0..col_len as isize)
+                    .map(|j| cells[(j - n).rem_euclid(col_len as isize) as usize])
+                    .collect();
+                assert_eq!(row(&rotated, 0), expected, "{} columns by {}", col_len, n);
+                assert_eq!(rotated.count_ones(), grid.count_ones());
+            }
+        }
+    }
+
+    #[test]
+    fn neighbor_counts() {
+        for &col_len in WIDTHS.iter() {
+            let grid = pattern(4, col_len, 7);
+            let counts = grid.neighbor_counts();
+            for (i, j) in counts.positions() {
+                let expected = (-1..=1)
+                    .flat_map(|di| (-1..=1).map(move |dj| (di, dj)))
+                    .filter(|&delta| delta != (0, 0))
+                    .filter_map(|(di, dj)| {
+                        let (x, y) = (i as isize + di, j as isize + dj);
+                        let inside = (0..4).contains(&x) && (0..col_len as isize).contains(&y);
+                        inside.then_some((x, y))
+                    })
+                    .filter(|&(x, y)| grid.get((x as usize, y as usize)))
+                    .count();
+                assert_eq!(counts[(i, j)] as usize, expected, "({}, {})", i, j);
+            }
+            for k in 0..=9 {
+                let at_least = grid.neighbors_at_least(k);
+                let expected = BitGrid::from_grid(&counts, |&count| count >= k);
+                assert_eq!(at_least, expected, "{} columns, k = {}", col_len, k);
+            }
+        }
+    }
+
+    #[test]
+    fn counter_planes() {
+        // Counter c of the 16 counters in the low bits holds the value c
+        let planes = (0..16).fold([0; 4], |planes, c| {
+            (0..c).fold(planes, |planes, _| add_bits(planes, 1 << c))
+        });
+        for k in 0..=16u8 {
+            let expected = (0..16).filter(|&c| c >= k).fold(0, |mask, c| mask | 1 << c);
+            assert_eq!(at_least(&planes, k) & 0xffff, expected, "k = {}", k);
+        }
+    }
+}
@@ -0,0 +1,177 @@
+// Encrypted-at-rest inputs ('dayXX.txt.enc'), so that puzzle inputs need not be published.
+//
+// The passphrase is read from the 'AOC_INPUT_KEY' environment variable, or else from the file
+// named by 'AOC_INPUT_KEY_FILE' (default: '.aoc-key'). Each file has its own random salt,
+// and its ChaCha20-Poly1305 key is PBKDF2-HMAC-SHA256 of the passphrase with that salt.
+//
+// File layout: MAGIC | 16-byte salt | 12-byte nonce | ciphertext with 16-byte tag
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use sha2::Sha256;
+
+use crate::error::{Error, Result};
+
+const MAGIC: &[u8] = b"AOCENC2\n";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+#[cfg(not(test))]
+const PBKDF2_ROUNDS: u32 = 600_000;
+// Unit tests check the file format, not the work factor, and are slow with the full count
+#[cfg(test)]
+const PBKDF2_ROUNDS: u32 = 1_000;
+pub const KEY_ENV: &str = "AOC_INPUT_KEY";
+pub const KEY_FILE_ENV: &str = "AOC_INPUT_KEY_FILE";
+pub const DEFAULT_KEY_FILE: &str = ".aoc-key";
+
+// The passphrase, from which a key is derived for each file
+pub struct InputKey(String);
+
+impl InputKey {
+    pub fn from_passphrase(passphrase: &str) -> Self {
+        InputKey(String::from(passphrase.trim()))
+    }
+
+    fn derive(&self, salt: &[u8]) -> Key {
+        let mut key = Key::default();
+        pbkdf2::pbkdf2_hmac::<Sha256>(self.0.as_bytes(), salt, PBKDF2_ROUNDS, &mut key);
+        key
+    }
+
+    pub fn load() -> Result<Self> {
+        if let Ok(passphrase) = env::var(KEY_ENV) {
+            return Ok(InputKey::from_passphrase(&passphrase));
+        }
+        let key_file = env::var(KEY_FILE_ENV).unwrap_or_else(|_| String::from(DEFAULT_KEY_FILE));
+        let passphrase = fs::read_to_string(&key_file).map_err(|e| Error::Crypto {
+            path: PathBuf::from(&key_file),
+            reason: format!("no key in ${} and cannot read key file ({})", KEY_ENV, e),
+        })?;
+        Ok(InputKey::from_passphrase(&passphrase))
+    }
+}
+
+// 'inputs/day01.txt' -> 'inputs/day01.txt.enc'
+pub fn encrypted_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".enc");
+    PathBuf::from(name)
+}
+
+pub fn encrypt(key: &InputKey, plaintext: &[u8]) -> Vec<u8> {
+    let mut salt_nonce = [0u8; SALT_LEN + NONCE_LEN];
+    getrandom::getrandom(&mut salt_nonce).expect("OS random number generator should be available");
+    let (salt, nonce) = salt_nonce.split_at(SALT_LEN);
+    let ciphertext = ChaCha20Poly1305::new(&key.derive(salt))
+        .encrypt(Nonce::from_slice(nonce), plaintext)
+        .expect("Encryption of in-memory data should not fail");
+    [MAGIC, &salt_nonce, &ciphertext].concat()
+}
+
+// 'path' is only used in error messages
+pub fn decrypt(key: &InputKey, data: &[u8], path: &Path) -> Result<Vec<u8>> {
+    let error = |reason: &str| Error::Crypto {
+        path: path.to_path_buf(),
+        reason: String::from(reason),
+    };
+    if data.len() < MAGIC.len() + SALT_LEN + NONCE_LEN || !data.starts_with(MAGIC) {
+        return Err(error("not an encrypted input file"));
+    }
+    let (salt, rest) = data[MAGIC.len()..].split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    ChaCha20Poly1305::new(&key.derive(salt))
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| error("decryption failed (wrong key or corrupted file)"))
+}
+
+// Reads 'path.enc' as a string
+pub fn read_encrypted(path: &Path) -> Result<String> {
+    let enc_path = encrypted_path(path);
+    let data = fs::read(&enc_path).map_err(|source| Error::Io {
+        path: enc_path.clone(),
+        source,
+    })?;
+    decrypt_string(&data, &enc_path)
+}
+
+// Decrypts an input with the key from 'InputKey::load'
+pub fn decrypt_string(data: &[u8], enc_path: &Path) -> Result<String> {
+    let plaintext = decrypt(&InputKey::load()?, data, enc_path)?;
+    String::from_utf8(plaintext).map_err(|_| Error::Crypto {
+        path: enc_path.to_path_buf(),
+        reason: String::from("decrypted input is not UTF-8"),
+    })
+}
+
+// Writes 'path.enc' next to 'path', returning its path
+pub fn encrypt_file(key: &InputKey, path: &Path) -> Result<PathBuf> {
+    let io_error = |path: &Path| {
+        let path = path.to_path_buf();
+        move |source| Error::Io { path, source }
+    };
+    let plaintext = fs::read(path).map_err(io_error(path))?;
+    let enc_path = encrypted_path(path);
+    fs::write(&enc_path, encrypt(key, &plaintext)).map_err(io_error(&enc_path))?;
+    Ok(enc_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path() -> PathBuf {
+        PathBuf::from("inputs/day01.txt.enc")
+    }
+
+    #[test]
+    fn round_trip() {
+        let key = InputKey::from_passphrase("correct horse\n");
+        let data = encrypt(&key, b"1721\n979\n");
+        assert!(data.starts_with(MAGIC));
+        assert_eq!(data.len(), MAGIC.len() + SALT_LEN + NONCE_LEN + 9 + 16);
+        // The passphrase is trimmed
+        let same_key = InputKey::from_passphrase("correct horse");
+        assert_eq!(decrypt(&same_key, &data, &path()).unwrap(), b"1721\n979\n");
+        // A fresh salt and nonce every time
+        assert_ne!(encrypt(&key, b"1721\n979\n"), data);
+    }
+
+    #[test]
+    fn wrong_passphrase() {
+        let data = encrypt(&InputKey::from_passphrase("right"), b"secret");
+        let error = decrypt(&InputKey::from_passphrase("wrong"), &data, &path()).unwrap_err();
+        assert!(error.to_string().contains("decryption failed"), "{}", error);
+    }
+
+    #[test]
+    fn damaged_files() {
+        let key = InputKey::from_passphrase("key");
+        let data = encrypt(&key, b"secret");
+        let decrypt_error = |data: &[u8]| decrypt(&key, data, &path()).unwrap_err().to_string();
+
+        // Cut inside the header, and inside the tag
+        let header_len = MAGIC.len() + SALT_LEN + NONCE_LEN;
+        assert!(decrypt_error(&data[..header_len - 1]).contains("not an encrypted input file"));
+        assert!(decrypt_error(&data[..data.len() - 1]).contains("decryption failed"));
+        assert!(decrypt_error(b"").contains("not an encrypted input file"));
+
+        let mut bad_magic = data.clone();
+        bad_magic[0] ^= 1;
+        assert!(decrypt_error(&bad_magic).contains("not an encrypted input file"));
+        let mut flipped = data;
+        let last = flipped.len() - 1;
+        flipped[last] ^= 1;
+        assert!(decrypt_error(&flipped).contains("decryption failed"));
+    }
+
+    #[test]
+    fn paths() {
+        assert_eq!(
+            encrypted_path(Path::new("inputs/day01.txt")),
+            PathBuf::from("inputs/day01.txt.enc")
+        );
+    }
+}
@@ -0,0 +1,38 @@
+// Inputs compiled into the binary with the 'embed-inputs' feature.
+// 'INPUTS' holds ("inputs/dayXX.txt", contents) pairs, generated by build.rs,
+// and 'ENCRYPTED_INPUTS' holds the same for 'inputs/dayXX.txt.enc' (see 'crypto').
+// Readers in 'io' take the compiled-in copy only for paths made by 'path' ("embedded:inputs/dayXX.txt").
+use std::path::{Path, PathBuf};
+
+include!(concat!(env!("OUT_DIR"), "/embedded_inputs.rs"));
+
+const PREFIX: &str = "embedded:";
+
+// Path which reads the compiled-in copy of 'path', if there is one
+pub fn path(path: &Path) -> Option<PathBuf> {
+    let name = path.to_str()?;
+    let found = get(path).is_some() || get_encrypted(path).is_some();
+    found.then(|| PathBuf::from(format!("{}{}", PREFIX, name)))
+}
+
+// 'path' without its "embedded:" prefix, or None if it is a file on disk
+pub fn strip(path: &Path) -> Option<&Path> {
+    path.to_str()?.strip_prefix(PREFIX).map(Path::new)
+}
+
+pub fn get(path: &Path) -> Option<&'static str> {
+    let path = path.to_str()?;
+    INPUTS
+        .iter()
+        .find(|&&(name, _)| name == path)
+        .map(|&(_, contents)| contents)
+}
+
+// Contents of 'path.enc', to be decrypted
+pub fn get_encrypted(path: &Path) -> Option<&'static [u8]> {
+    let path = path.to_str()?;
+    ENCRYPTED_INPUTS
+        .iter()
+        .find(|&&(name, _)| name == path)
+        .map(|&(_, data)| data)
+}
@@ -0,0 +1,111 @@
+use std::error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        line: usize, // 1-based
+        text: String,
+        source: Box<dyn error::Error + Send + Sync>,
+    },
+    Records {
+        path: PathBuf,
+        errors: Vec<RecordError>,
+    },
+    Crypto {
+        path: PathBuf,
+        reason: String,
+    },
+    Manifest {
+        path: PathBuf,
+        reason: String,
+    },
+}
+
+// Failure of a nom parser on one record (line or section) of an input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordError {
+    pub line: usize,   // 1-based
+    pub column: usize, // 1-based, where parsing stopped
+    pub text: String,  // The line in which parsing stopped
+    pub message: String,
+}
+
+impl RecordError {
+    // Error at byte 'offset' of 'text', located by the line containing it
+    pub fn at(text: &str, offset: usize, message: &str) -> Self {
+        let line_start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = text[offset..].find('\n').map_or(text.len(), |i| offset + i);
+        RecordError {
+            line: text[..offset].matches('\n').count() + 1,
+            column: text[line_start..offset].chars().count() + 1,
+            text: String::from(text[line_start..line_end].trim_end_matches('\r')),
+            message: String::from(message),
+        }
+    }
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )?;
+        writeln!(f, "    {}", self.text)?;
+        write!(f, "    {:>width$}", "^", width = self.column)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            Error::Parse {
+                path,
+                line,
+                text,
+                source,
+            } => write!(
+                f,
+                "{}:{}: cannot parse {:?}: {}",
+                path.display(),
+                line,
+                text,
+                source
+            ),
+            Error::Records { path, errors } => {
+                write!(
+                    f,
+                    "{}: {} record(s) failed to parse",
+                    path.display(),
+                    errors.len()
+                )?;
+                errors.iter().try_for_each(|e| write!(f, "\n{}", e))
+            }
+            Error::Crypto { path, reason } => write!(f, "{}: {}", path.display(), reason),
+            Error::Manifest { path, reason } => write!(f, "{}: {}", path.display(), reason),
+        }
+    }
+}
+
+impl error::Error for RecordError {}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Parse { source, .. } => Some(source.as_ref()),
+            Error::Records { .. } | Error::Crypto { .. } | Error::Manifest { .. } => None,
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use std::fmt;
+use std::fmt::Display;
+
+use crate::grid::Grid;
+
+// (row, column), which may be negative
+pub type SignedPos = (i64, i64);
+
+// Grid with an offset origin, which grows in any direction on write.
+// Cells never written read as 'default'.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpandingGrid<T> {
+    grid: Grid<T>,
+    origin: SignedPos, // Position of 'grid.vec[0][0]'
+    default: T,
+}
+
+impl<T: Clone> ExpandingGrid<T> {
+    pub fn new(default: T) -> Self {
+        ExpandingGrid {
+            grid: Grid::from(Vec::new()),
+            origin: (0, 0),
+            default,
+        }
+    }
+
+    // 'grid.vec[0][0]' is placed at (0, 0)
+    pub fn from_grid(grid: Grid<T>, default: T) -> Self {
+        ExpandingGrid {
+            grid,
+            origin: (0, 0),
+            default,
+        }
+    }
+
+    pub fn get(&self, pos: SignedPos) -> &T {
+        self.local(pos)
+            .and_then(|(i, j)| self.grid.get((i, j)))
+            .unwrap_or(&self.default)
+    }
+
+    pub fn get_mut(&mut self, pos: SignedPos) -> &mut T {
+        self.expand_to(pos);
+        let (i, j) = self.local(pos).expect("Should be expanded");
+        &mut self.grid.vec[i][j]
+    }
+
+    pub fn set(&mut self, pos: SignedPos, value: T) {
+        *self.get_mut(pos) = value;
+    }
+
+    // Inclusive (top-left, bottom-right) corners, or None if nothing has been stored
+    pub fn bounding_box(&self) -> Option<(SignedPos, SignedPos)> {
+        let (row_len, col_len) = self.grid.size();
+        if row_len == 0 || col_len == 0 {
+            return None;
+        }
+        let (x, y) = self.origin;
+        Some(((x, y), (x + row_len as i64 - 1, y + col_len as i64 - 1)))
+    }
+
+    pub fn contains(&self, pos: SignedPos) -> bool {
+        self.local(pos).is_some()
+    }
+
+    pub fn origin(&self) -> SignedPos {
+        self.origin
+    }
+
+    // Cells within the bounding box
+    pub fn as_grid(&self) -> &Grid<T> {
+        &self.grid
+    }
+
+    pub fn into_grid(self) -> Grid<T> {
+        self.grid
+    }
+
+    // Grows the grid so that it contains 'pos'
+    pub fn expand_to(&mut self, pos: SignedPos) {
+        let ((top, left), (bottom, right)) = match self.bounding_box() {
+            Some(bbox) => bbox,
+            None => {
+                self.grid = Grid::from(vec![vec![self.default.clone()]]);
+                self.origin = pos;
+                return;
+            }
+        };
+        let (x, y) = pos;
+        let grow_top = (top - x).max(0) as usize;
+        let grow_bottom = (x - bottom).max(0) as usize;
+        let grow_left = (left - y).max(0) as usize;
+        let grow_right = (y - right).max(0) as usize;
+        if grow_top + grow_bottom + grow_left + grow_right == 0 {
+            return;
+        }
+
+        let d = &self.default;
+        let col_len = self.grid.size().1 + grow_left + grow_right;
+        let old_rows = std::mem::take(&mut self.grid.vec);
+        let mut rows = vec![vec![d.clone(); col_len]; grow_top];
+        rows.extend(old_rows.into_iter().map(|line| {
+            let mut row = vec![d.clone(); grow_left];
+            row.extend(line);
+            row.resize(col_len, d.clone());
+            row
+        }));
+        rows.resize(rows.len() + grow_bottom, vec![d.clone(); col_len]);
+        self.grid = Grid::from(rows);
+        self.origin = (top - grow_top as i64, left - grow_left as i64);
+    }
+
+    fn local(&self, (x, y): SignedPos) -> Option<(usize, usize)> {
+        let (row_len, col_len) = self.grid.size();
+        let i = x - self.origin.0;
+        let j = y - self.origin.1;
+        if i < 0 || j < 0 || i >= row_len as i64 || j >= col_len as i64 {
+            None
+        } else {
+            Some((i as usize, j as usize))
+        }
+    }
+}
+
+impl<T: Display> Display for ExpandingGrid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.grid.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(grid: &ExpandingGrid<char>) -> Vec<String> {
+        grid.as_grid()
+            .vec
+            .iter()
+            .map(|row| row.iter().collect())
+            .collect()
+    }
+
+    #[test]
+    fn empty() {
+        let mut grid = ExpandingGrid::new('.');
+        assert_eq!(grid.bounding_box(), None);
+        assert_eq!(grid.get((3, -4)), &'.');
+        assert!(!grid.contains((0, 0)));
+        grid.set((3, -4), '#');
+        assert_eq!(grid.bounding_box(), Some(((3, -4), (3, -4))));
+        assert_eq!(grid.origin(), (3, -4));
+    }
+
+    #[test]
+    fn grows_in_every_direction() {
+        let mut grid = ExpandingGrid::from_grid(Grid::from(vec![vec!['a']]), '.');
+        grid.set((-1, 0), 't');
+        assert_eq!(rows(&grid), vec!["t", "a"]);
+        grid.set((1, 0), 'b');
+        assert_eq!(rows(&grid), vec!["t", "a", "b"]);
+        grid.set((0, -2), 'l');
+        assert_eq!(rows(&grid), vec!["..t", "l.a", "..b"]);
+        grid.set((0, 1), 'r');
+        assert_eq!(rows(&grid), vec!["..t.", "l.ar", "..b."]);
+        assert_eq!(grid.bounding_box(), Some(((-1, -2), (1, 1))));
+        assert_eq!(grid.origin(), (-1, -2));
+    }
+
+    #[test]
+    fn grows_diagonally() {
+        let mut grid = ExpandingGrid::from_grid(Grid::from(vec![vec!['a']]), '.');
+        grid.set((-1, -1), 'x');
+        grid.set((2, 2), 'y');
+        assert_eq!(rows(&grid), vec!["x...", ".a..", "....", "...y"]);
+        assert_eq!(grid.get((0, 0)), &'a');
+        assert_eq!(grid.get((2, 2)), &'y');
+        assert_eq!(grid.get((5, 5)), &'.');
+        // Writing inside the bounding box doesn't grow it
+        grid.set((1, 0), 'z');
+        assert_eq!(grid.bounding_box(), Some(((-1, -1), (2, 2))));
+    }
+}
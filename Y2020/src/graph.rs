@@ -0,0 +1,201 @@
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use petgraph::graph::NodeIndex;
+use petgraph::visit::{Dfs, EdgeRef, Reversed};
+use petgraph::{Direction, Graph};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    Dot,
+    GraphMl,
+}
+impl GraphFormat {
+    // By the extension of 'path': '.dot' or '.gv', or '.graphml'
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "dot" | "gv" => Some(GraphFormat::Dot),
+            "graphml" => Some(GraphFormat::GraphMl),
+            _ => None,
+        }
+    }
+}
+
+// Nodes and edges are labelled by their weights
+pub fn write_graph<N, E>(graph: &Graph<N, E>, format: GraphFormat, path: &Path) -> io::Result<()>
+where
+    N: Display,
+    E: Display,
+{
+    let text = match format {
+        GraphFormat::Dot => encode_dot(graph),
+        GraphFormat::GraphMl => encode_graphml(graph),
+    };
+    fs::write(path, text)
+}
+
+pub fn encode_dot<N: Display, E: Display>(graph: &Graph<N, E>) -> String {
+    let quote = |s: String| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+    let nodes = graph.node_indices().map(|i| {
+        format!(
+            "    {} [label={}];\n",
+            i.index(),
+            quote(graph[i].to_string())
+        )
+    });
+    let edges = graph.edge_references().map(|e| {
+        let (from, to) = (e.source().index(), e.target().index());
+        format!(
+            "    {} -> {} [label={}];\n",
+            from,
+            to,
+            quote(e.weight().to_string())
+        )
+    });
+    let body: String = nodes.chain(edges).collect();
+    format!("digraph {{\n{}}}\n", body)
+}
+
+pub fn encode_graphml<N: Display, E: Display>(graph: &Graph<N, E>) -> String {
+    let escape = |s: String| {
+        s.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    };
+    let nodes = graph.node_indices().map(|i| {
+        let label = escape(graph[i].to_string());
+        format!(
+            "    <node id=\"n{}\"><data key=\"node_label\">{}</data></node>\n",
+            i.index(),
+            label
+        )
+    });
+    let edges = graph.edge_references().map(|e| {
+        let (from, to) = (e.source().index(), e.target().index());
+        let weight = escape(e.weight().to_string());
+        format!(
+            "    <edge source=\"n{}\" target=\"n{}\"><data key=\"edge_label\">{}</data></edge>\n",
+            from, to, weight
+        )
+    });
+    let body: String = nodes.chain(edges).collect();
+    format!(
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"node_label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n",
+            "  <key id=\"edge_label\" for=\"edge\" attr.name=\"label\" attr.type=\"string\"/>\n",
+            "  <graph id=\"G\" edgedefault=\"directed\">\n",
+            "{}",
+            "  </graph>\n",
+            "</graphml>\n"
+        ),
+        body
+    )
+}
+
+// 'start' and the nodes reachable from it ('Outgoing'), or reaching it ('Incoming')
+pub fn reachable<N, E>(
+    graph: &Graph<N, E>,
+    start: NodeIndex,
+    direction: Direction,
+) -> HashSet<NodeIndex> {
+    let mut found = HashSet::new();
+    match direction {
+        Direction::Outgoing => {
+            let mut dfs = Dfs::new(graph, start);
+            while let Some(i) = dfs.next(graph) {
+                found.insert(i);
+            }
+        }
+        Direction::Incoming => {
+            let reversed = Reversed(graph);
+            let mut dfs = Dfs::new(reversed, start);
+            while let Some(i) = dfs.next(reversed) {
+                found.insert(i);
+            }
+        }
+    }
+    found
+}
+
+// Nodes in 'keep' and the edges between them. Node indices are renumbered.
+pub fn subgraph<N, E>(graph: &Graph<N, E>, keep: &HashSet<NodeIndex>) -> Graph<N, E>
+where
+    N: Clone,
+    E: Clone,
+{
+    graph.filter_map(
+        |i, node| keep.contains(&i).then(|| node.clone()),
+        |_, edge| Some(edge.clone()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a -> b -> c, a -> c
+    fn labelled(a: &'static str) -> Graph<&'static str, &'static str> {
+        let mut graph = Graph::new();
+        let (a, b, c) = (graph.add_node(a), graph.add_node("b"), graph.add_node("c"));
+        graph.add_edge(a, b, "1");
+        graph.add_edge(b, c, "2");
+        graph.add_edge(a, c, "3");
+        graph
+    }
+
+    #[test]
+    fn dot_escapes_labels() {
+        let mut graph = labelled(r#"say "hi" \ bye"#);
+        let (a, b) = (NodeIndex::new(0), NodeIndex::new(1));
+        graph.update_edge(a, b, r#"\""#);
+        let dot = encode_dot(&graph);
+        assert!(dot.starts_with("digraph {\n"));
+        assert!(dot.contains(r#"    0 [label="say \"hi\" \\ bye"];"#));
+        assert!(dot.contains(r#"    0 -> 1 [label="\\\""];"#));
+        assert!(dot.contains(r#"    1 -> 2 [label="2"];"#));
+    }
+
+    #[test]
+    fn graphml_escapes_labels() {
+        let mut graph = labelled(r#"<a & "b">"#);
+        let (a, b) = (NodeIndex::new(0), NodeIndex::new(1));
+        graph.update_edge(a, b, "1 < 2 & 3");
+        let graphml = encode_graphml(&graph);
+        assert!(graphml.contains(
+            r#"<node id="n0"><data key="node_label">&lt;a &amp; &quot;b&quot;&gt;</data></node>"#
+        ));
+        assert!(graphml.contains(
+            r#"<edge source="n0" target="n1"><data key="edge_label">1 &lt; 2 &amp; 3</data></edge>"#
+        ));
+    }
+
+    #[test]
+    fn subgraph_drops_edges_to_dropped_nodes() {
+        let graph = labelled("a");
+        let keep: HashSet<NodeIndex> = [0, 2].iter().map(|&i| NodeIndex::new(i)).collect();
+        let sub = subgraph(&graph, &keep);
+        let nodes: Vec<&str> = sub.node_indices().map(|i| sub[i]).collect();
+        assert_eq!(nodes, vec!["a", "c"]);
+        let edges: Vec<(&str, &str, &str)> = sub
+            .edge_references()
+            .map(|e| (sub[e.source()], sub[e.target()], *e.weight()))
+            .collect();
+        assert_eq!(edges, vec![("a", "c", "3")]);
+    }
+
+    #[test]
+    fn reachable_both_ways() {
+        let graph = labelled("a");
+        let (a, b, c) = (NodeIndex::new(0), NodeIndex::new(1), NodeIndex::new(2));
+        let from_b = reachable(&graph, b, Direction::Outgoing);
+        assert_eq!(from_b, [b, c].iter().copied().collect());
+        let to_b = reachable(&graph, b, Direction::Incoming);
+        assert_eq!(to_b, [a, b].iter().copied().collect());
+    }
+}
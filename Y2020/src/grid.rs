@@ -1,6 +1,11 @@
 use std::fmt;
 use std::fmt::Display;
+use std::ops::{Index, IndexMut};
 
+// (row, column)
+pub type Pos = (usize, usize);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Grid<T> {
     pub vec: Vec<Vec<T>>,
 }
@@ -12,6 +17,158 @@ impl<T> Grid<T> {
             (self.vec.len(), self.vec[0].len())
         }
     }
+    pub fn get(&self, (i, j): Pos) -> Option<&T> {
+        self.vec.get(i)?.get(j)
+    }
+    // Moves 'pos' by (di, dj), returning None when it leaves the grid
+    pub fn offset(&self, (i, j): Pos, (di, dj): (i32, i32)) -> Option<Pos> {
+        let (row_len, col_len) = self.size();
+        let x = i as i64 + di as i64;
+        let y = j as i64 + dj as i64;
+        if x < 0 || y < 0 || x >= row_len as i64 || y >= col_len as i64 {
+            None
+        } else {
+            Some((x as usize, y as usize))
+        }
+    }
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let (row_len, col_len) = self.size();
+        (0..row_len).flat_map(move |i| (0..col_len).map(move |j| (i, j)))
+    }
+}
+
+// Region analysis //
+
+const DELTAS_4: [(i32, i32); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+
+// 4-directionally connected cells
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub cells: Vec<Pos>,
+    // Inclusive (top-left, bottom-right) corners
+    pub bounding_box: (Pos, Pos),
+    pub touches_border: bool,
+}
+impl Region {
+    pub fn size(&self) -> usize {
+        self.cells.len()
+    }
+    // Surrounded by other cells, i.e. not reaching the edge of the grid
+    pub fn is_enclosed(&self) -> bool {
+        !self.touches_border
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn neighbors4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        DELTAS_4
+            .iter()
+            .filter_map(move |&delta| self.offset(pos, delta))
+    }
+
+    // Region of cells satisfying 'predicate' reachable from 'start',
+    // or None if 'start' itself doesn't satisfy it
+    pub fn flood_fill<P>(&self, start: Pos, predicate: P) -> Option<Region>
+    where
+        P: Fn(&T) -> bool,
+    {
+        if !predicate(self.get(start)?) {
+            return None;
+        }
+        let mut visited = self.map_cells(|_| false);
+        Some(self.fill_region(start, &predicate, &mut visited))
+    }
+
+    // Every maximal region of cells satisfying 'predicate', in row-major order of their first cell
+    pub fn components<P>(&self, predicate: P) -> Vec<Region>
+    where
+        P: Fn(&T) -> bool,
+    {
+        let mut visited = self.map_cells(|_| false);
+        self.positions()
+            .filter_map(|pos| {
+                if visited[pos] || !predicate(&self[pos]) {
+                    None
+                } else {
+                    Some(self.fill_region(pos, &predicate, &mut visited))
+                }
+            })
+            .collect()
+    }
+
+    // Regions which don't touch the border of the grid
+    pub fn enclosed_regions<P>(&self, predicate: P) -> Vec<Region>
+    where
+        P: Fn(&T) -> bool,
+    {
+        self.components(predicate)
+            .into_iter()
+            .filter(Region::is_enclosed)
+            .collect()
+    }
+
+    pub fn map_cells<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: Fn(&T) -> U,
+    {
+        Grid::from(
+            self.vec
+                .iter()
+                .map(|line| line.iter().map(&f).collect())
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    // DFS with an explicit stack, so that large regions don't overflow the call stack
+    fn fill_region<P>(&self, start: Pos, predicate: &P, visited: &mut Grid<bool>) -> Region
+    where
+        P: Fn(&T) -> bool,
+    {
+        let mut cells = Vec::new();
+        let mut stack = vec![start];
+        visited[start] = true;
+        while let Some(pos) = stack.pop() {
+            cells.push(pos);
+            self.neighbors4(pos).for_each(|next| {
+                if !visited[next] && predicate(&self[next]) {
+                    visited[next] = true;
+                    stack.push(next);
+                }
+            });
+        }
+        cells.sort();
+        self.region_of(cells)
+    }
+
+    fn region_of(&self, cells: Vec<Pos>) -> Region {
+        let (row_len, col_len) = self.size();
+        let ((min_i, min_j), (max_i, max_j)) = cells.iter().fold(
+            ((usize::MAX, usize::MAX), (0, 0)),
+            |((min_i, min_j), (max_i, max_j)), &(i, j)| {
+                ((min_i.min(i), min_j.min(j)), (max_i.max(i), max_j.max(j)))
+            },
+        );
+        let touches_border =
+            min_i == 0 || min_j == 0 || max_i + 1 == row_len || max_j + 1 == col_len;
+        Region {
+            cells,
+            bounding_box: ((min_i, min_j), (max_i, max_j)),
+            touches_border,
+        }
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+    fn index(&self, (i, j): Pos) -> &T {
+        &self.vec[i][j]
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, (i, j): Pos) -> &mut T {
+        &mut self.vec[i][j]
+    }
 }
 
 impl<T> From<Vec<Vec<T>>> for Grid<T> {
@@ -0,0 +1,287 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::iter;
+use std::path::{Path, PathBuf};
+
+use crate::grid::Grid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Ppm,
+    Png,
+}
+impl ImageFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Png => "png",
+        }
+    }
+}
+
+// Each grid cell is drawn as a 'scale' x 'scale' block of pixels
+pub fn write_image<T, F>(
+    grid: &Grid<T>,
+    color: F,
+    scale: usize,
+    format: ImageFormat,
+    path: &Path,
+) -> io::Result<()>
+where
+    F: Fn(&T) -> Rgb,
+{
+    let image = rasterize(grid, color, scale.max(1));
+    let mut writer = BufWriter::new(File::create(path)?);
+    match format {
+        ImageFormat::Ppm => encode_ppm(&image, &mut writer)?,
+        ImageFormat::Png => encode_png(&image, &mut writer)?,
+    };
+    writer.flush()
+}
+
+pub fn write_ppm<T, F>(grid: &Grid<T>, color: F, path: &Path) -> io::Result<()>
+where
+    F: Fn(&T) -> Rgb,
+{
+    write_image(grid, color, 1, ImageFormat::Ppm, path)
+}
+
+pub fn write_png<T, F>(grid: &Grid<T>, color: F, path: &Path) -> io::Result<()>
+where
+    F: Fn(&T) -> Rgb,
+{
+    write_image(grid, color, 1, ImageFormat::Png, path)
+}
+
+// Dumps numbered frames ('frame_00000.png', 'frame_00001.png', ...) into a directory,
+// e.g. for 'ffmpeg -i frame_%05d.png out.gif'
+pub struct FrameWriter {
+    dir: PathBuf,
+    format: ImageFormat,
+    scale: usize,
+    frame: usize,
+}
+impl FrameWriter {
+    pub fn new(dir: &Path, format: ImageFormat, scale: usize) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        Ok(FrameWriter {
+            dir: dir.to_path_buf(),
+            format,
+            scale,
+            frame: 0,
+        })
+    }
+    pub fn write<T, F>(&mut self, grid: &Grid<T>, color: F) -> io::Result<PathBuf>
+    where
+        F: Fn(&T) -> Rgb,
+    {
+        let file_name = format!("frame_{:05}.{}", self.frame, self.format.extension());
+        let path = self.dir.join(file_name);
+        write_image(grid, color, self.scale, self.format, &path)?;
+        self.frame += 1;
+        Ok(path)
+    }
+    pub fn frame_count(&self) -> usize {
+        self.frame
+    }
+}
+
+// Rasterizing //
+
+struct Image {
+    width: usize,
+    height: usize,
+    rgb: Vec<u8>, // Row-major, 3 bytes per pixel
+}
+
+fn rasterize<T, F>(grid: &Grid<T>, color: F, scale: usize) -> Image
+where
+    F: Fn(&T) -> Rgb,
+{
+    let (rows, cols) = grid.size();
+    let rgb = grid
+        .vec
+        .iter()
+        .flat_map(|line| {
+            let pixel_row: Vec<u8> = line
+                .iter()
+                .flat_map(|x| {
+                    let Rgb(r, g, b) = color(x);
+                    iter::repeat_n([r, g, b], scale).flatten()
+                })
+                .collect();
+            iter::repeat_n(pixel_row, scale).flatten()
+        })
+        .collect();
+    Image {
+        width: cols * scale,
+        height: rows * scale,
+        rgb,
+    }
+}
+
+// PPM (binary, P6) //
+
+fn encode_ppm<W: Write>(image: &Image, w: &mut W) -> io::Result<()> {
+    write!(w, "P6\n{} {}\n255\n", image.width, image.height)?;
+    w.write_all(&image.rgb)
+}
+
+// PNG //
+// 8-bit truecolor, no filtering, and uncompressed ("stored") deflate blocks,
+// which keeps the encoder free of dependencies at the cost of file size.
+
+fn encode_png<W: Write>(image: &Image, w: &mut W) -> io::Result<()> {
+    w.write_all(b"\x89PNG\r\n\x1a\n")?;
+
+    let mut header = Vec::new();
+    header.extend_from_slice(&(image.width as u32).to_be_bytes());
+    header.extend_from_slice(&(image.height as u32).to_be_bytes());
+    // Bit depth 8, color type 2 (RGB), default compression/filter, no interlace
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+    write_png_chunk(w, b"IHDR", &header)?;
+
+    // Every scanline starts with its filter type (0: None)
+    let stride = image.width * 3;
+    let scanlines: Vec<u8> = (0..image.height)
+        .flat_map(|y| iter::once(0).chain(image.rgb[y * stride..(y + 1) * stride].iter().cloned()))
+        .collect();
+    write_png_chunk(w, b"IDAT", &zlib_stored(&scanlines))?;
+
+    write_png_chunk(w, b"IEND", &[])
+}
+
+fn write_png_chunk<W: Write>(w: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    w.write_all(&(data.len() as u32).to_be_bytes())?;
+    w.write_all(kind)?;
+    w.write_all(data)?;
+    let crc = crc32(kind.iter().chain(data.iter()));
+    w.write_all(&crc.to_be_bytes())
+}
+
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 0xffff;
+    let mut out = vec![0x78, 0x01];
+    let block_count = data.len().div_ceil(MAX_BLOCK);
+    if block_count == 0 {
+        // A single empty final block
+        out.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    data.chunks(MAX_BLOCK).enumerate().for_each(|(i, block)| {
+        let is_final = i + 1 == block_count;
+        let len = block.len() as u16;
+        out.push(if is_final { 1 } else { 0 });
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    });
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32<'a, I: Iterator<Item = &'a u8>>(bytes: I) -> u32 {
+    let crc = bytes.fold(0xffff_ffffu32, |crc, &byte| {
+        (0..8).fold(crc ^ byte as u32, |c, _| {
+            if c & 1 == 1 {
+                0xedb8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            }
+        })
+    });
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (a, b) = bytes.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + byte as u32) % MOD;
+        (a, (b + a) % MOD)
+    });
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2 x 1 cells: red, blue
+    fn image(scale: usize) -> Image {
+        let grid = Grid {
+            vec: vec![vec![true, false]],
+        };
+        let color = |&x: &bool| if x { Rgb(255, 0, 0) } else { Rgb(0, 0, 255) };
+        rasterize(&grid, color, scale)
+    }
+
+    // (kind, data) of each chunk, checking their lengths and CRCs
+    fn png_chunks(png: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let mut chunks = vec![];
+        let mut rest = png;
+        while !rest.is_empty() {
+            let len = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+            let (kind, data) = (&rest[4..8], &rest[8..8 + len]);
+            let crc = &rest[8 + len..12 + len];
+            assert_eq!(crc, &crc32(kind.iter().chain(data.iter())).to_be_bytes());
+            chunks.push((String::from_utf8(kind.to_vec()).unwrap(), data.to_vec()));
+            rest = &rest[12 + len..];
+        }
+        chunks
+    }
+
+    #[test]
+    fn checksums() {
+        assert_eq!(crc32(b"123456789".iter()), 0xcbf4_3926);
+        assert_eq!(crc32(b"".iter()), 0);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+        assert_eq!(adler32(b""), 1);
+    }
+
+    #[test]
+    fn ppm() {
+        let mut ppm = Vec::new();
+        encode_ppm(&image(2), &mut ppm).unwrap();
+        let header = b"P6\n4 2\n255\n";
+        assert_eq!(&ppm[..header.len()], header);
+        let row = [255, 0, 0, 255, 0, 0, 0, 0, 255, 0, 0, 255];
+        assert_eq!(&ppm[header.len()..], [row, row].concat().as_slice());
+    }
+
+    #[test]
+    fn png() {
+        let mut png = Vec::new();
+        encode_png(&image(1), &mut png).unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        // The IEND chunk is always the same
+        assert_eq!(&png[png.len() - 12..], b"\0\0\0\0IEND\xae\x42\x60\x82");
+
+        let chunks = png_chunks(&png[8..]);
+        let kinds: Vec<&str> = chunks.iter().map(|(kind, _)| kind.as_str()).collect();
+        assert_eq!(kinds, vec!["IHDR", "IDAT", "IEND"]);
+        assert_eq!(chunks[0].1, vec![0, 0, 0, 2, 0, 0, 0, 1, 8, 2, 0, 0, 0]);
+
+        // zlib header, one final stored block of the filtered scanline, and its Adler-32
+        let scanline = [0, 255, 0, 0, 0, 0, 255];
+        let idat = &chunks[1].1;
+        assert_eq!(&idat[..7], &[0x78, 0x01, 1, 7, 0, !7, 0xff]);
+        assert_eq!(&idat[7..14], &scanline);
+        assert_eq!(&idat[14..], &adler32(&scanline).to_be_bytes());
+    }
+
+    #[test]
+    fn stored_blocks() {
+        let data = vec![7; 0xffff + 1];
+        let zlib = zlib_stored(&data);
+        // Two blocks, only the second final
+        assert_eq!(&zlib[2..7], &[0, 0xff, 0xff, 0, 0]);
+        assert_eq!(&zlib[7 + 0xffff..7 + 0xffff + 5], &[1, 1, 0, 0xfe, 0xff]);
+        assert_eq!(zlib.len(), 2 + 5 + 0xffff + 5 + 1 + 4);
+        assert_eq!(
+            zlib_stored(&[]),
+            vec![0x78, 0x01, 1, 0, 0, 0xff, 0xff, 0, 0, 0, 1]
+        );
+    }
+}
@@ -1,24 +1,41 @@
 use std::fs::File;
-use std::io::{BufRead, BufReader, Read};
+use std::io::Read;
 use std::iter::Iterator;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
+use crate::crypto;
+use crate::embedded;
+use crate::error::{Error, RecordError, Result};
 use crate::grid::Grid;
+use crate::manifest;
+use crate::nom::{self as aoc_nom, ParseResult};
+
+// Panicking readers, for use in 'main' //
 
 pub fn read_file_line(path: &Path) -> impl Iterator<Item = String> {
-    let file = File::open(path).expect("File open error");
-    let reader = BufReader::new(file);
-    reader.lines().map(|r| r.expect("File read error"))
+    or_panic(try_read_file_line(path)).into_iter()
 }
 
+// Unlike 'try_read_file_vec', any 'FromStr' type whose error is 'Debug' can be read
 pub fn read_file_vec<T>(path: &Path) -> Vec<T>
 where
     T: FromStr,
     T::Err: std::fmt::Debug,
 {
     read_file_line(path)
-        .map(|s| s.parse().expect("Parse error"))
+        .enumerate()
+        .map(|(i, s)| {
+            s.parse().unwrap_or_else(|e| {
+                panic!(
+                    "{}:{}: cannot parse {:?}: {:?}",
+                    path.display(),
+                    i + 1,
+                    s,
+                    e
+                )
+            })
+        })
         .collect()
 }
 
@@ -26,19 +43,369 @@ pub fn read_file_grid<T>(path: &Path) -> Grid<T>
 where
     T: From<char>,
 {
-    let vec: Vec<Vec<_>> = read_file_line(path)
+    or_panic(try_read_file_grid(path))
+}
+
+// Returns blank-line separated sections, with their lines joined by newline
+pub fn read_file_blankline(path: &Path) -> impl Iterator<Item = String> {
+    or_panic(try_read_file_blankline(path)).into_iter()
+}
+
+pub fn read_file_sections(path: &Path) -> Vec<Vec<String>> {
+    or_panic(try_read_file_sections(path))
+}
+
+pub fn read_file_headed_sections(path: &Path) -> Vec<Section> {
+    or_panic(try_read_file_headed_sections(path))
+}
+
+fn or_panic<T>(result: Result<T>) -> T {
+    result.unwrap_or_else(|e| panic!("{}", e))
+}
+
+// Whole input in a single buffer //
+// Lines, sections and grid rows borrow from the buffer, so parsers can return '&str's
+
+pub struct Input {
+    path: PathBuf,
+    text: String,
+}
+
+impl Input {
+    pub fn read(path: &Path) -> Self {
+        or_panic(Input::try_read(path))
+    }
+
+    pub fn try_read(path: &Path) -> Result<Self> {
+        let text = try_read_file_string(path)?;
+        Ok(Input {
+            path: path.to_path_buf(),
+            text,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.text.lines()
+    }
+
+    // Blank-line separated sections, as lists of lines (see 'try_read_file_sections')
+    pub fn sections(&self) -> Vec<Vec<&str>> {
+        split_sections(&self.text)
+    }
+
+    // Blank-line separated sections, each as one slice spanning its lines
+    // (line endings are kept as in the file)
+    pub fn blocks(&self) -> Vec<&str> {
+        let offset = |s: &str| s.as_ptr() as usize - self.text.as_ptr() as usize;
+        self.sections()
+            .iter()
+            .map(|lines| {
+                let first = lines[0];
+                let last = lines[lines.len() - 1];
+                &self.text[offset(first)..offset(last) + last.len()]
+            })
+            .collect()
+    }
+
+    // 1-based line on which 'slice' (borrowed from this input) starts
+    pub fn line_number(&self, slice: &str) -> Option<usize> {
+        aoc_nom::offset(&self.text, slice)
+            .map(|offset| self.text[..offset].matches('\n').count() + 1)
+    }
+
+    // Lines of a grid, without trailing blank lines
+    pub fn grid_rows(&self) -> Vec<&str> {
+        let mut rows: Vec<&str> = self.lines().collect();
+        while rows.last().is_some_and(|row| row.trim().is_empty()) {
+            rows.pop();
+        }
+        rows
+    }
+}
+
+// Records parsed by nom //
+// Every failing record is reported, with its line number and where in it parsing stopped
+
+impl Input {
+    // Applies 'parser' to every non-blank line
+    pub fn records<'a, T, P>(&'a self, parser: P) -> Vec<T>
+    where
+        P: FnMut(&'a str) -> ParseResult<'a, T>,
+    {
+        or_panic(self.try_records(parser))
+    }
+
+    pub fn try_records<'a, T, P>(&'a self, parser: P) -> Result<Vec<T>>
+    where
+        P: FnMut(&'a str) -> ParseResult<'a, T>,
+    {
+        let lines = self.lines().filter(|line| !line.trim().is_empty());
+        self.parse_records(lines, parser)
+    }
+
+    // Applies 'parser' to every blank-line separated section (see 'blocks')
+    pub fn section_records<'a, T, P>(&'a self, parser: P) -> Vec<T>
+    where
+        P: FnMut(&'a str) -> ParseResult<'a, T>,
+    {
+        or_panic(self.try_section_records(parser))
+    }
+
+    pub fn try_section_records<'a, T, P>(&'a self, parser: P) -> Result<Vec<T>>
+    where
+        P: FnMut(&'a str) -> ParseResult<'a, T>,
+    {
+        self.parse_records(self.blocks().into_iter(), parser)
+    }
+
+    fn parse_records<'a, T, P, I>(&'a self, records: I, mut parser: P) -> Result<Vec<T>>
+    where
+        P: FnMut(&'a str) -> ParseResult<'a, T>,
+        I: Iterator<Item = &'a str>,
+    {
+        let (values, errors): (Vec<_>, Vec<_>) = records
+            .map(|record| match parser(record) {
+                Ok((rest, value)) if rest.trim().is_empty() => Ok(value),
+                Ok((rest, _)) => Err(self.record_error(record, rest, "unexpected trailing input")),
+                Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => {
+                    let (rest, message) = aoc_nom::describe(&e);
+                    Err(self.record_error(record, rest, &message))
+                }
+                Err(nom::Err::Incomplete(_)) => {
+                    let end = &record[record.len()..];
+                    Err(self.record_error(record, end, "unexpected end of record"))
+                }
+            })
+            .partition(|r| r.is_ok());
+        if errors.is_empty() {
+            Ok(values.into_iter().map(|r| r.ok().unwrap()).collect())
+        } else {
+            Err(Error::Records {
+                path: self.path.clone(),
+                errors: errors.into_iter().map(|r| r.err().unwrap()).collect(),
+            })
+        }
+    }
+
+    // 'rest' is the unparsed remainder of 'record', which is a slice of the buffer
+    fn record_error(&self, record: &str, rest: &str, message: &str) -> RecordError {
+        let record_start = aoc_nom::offset(&self.text, record).unwrap_or(0);
+        // Parsers may return slices not borrowed from 'record'
+        let offset = aoc_nom::offset(record, rest).map_or(record_start, |i| record_start + i);
+        RecordError::at(&self.text, offset, message)
+    }
+}
+
+// Same as 'Input::records', for records which don't borrow from the input
+pub fn read_records<T, P>(path: &Path, parser: P) -> Vec<T>
+where
+    P: for<'a> FnMut(&'a str) -> ParseResult<'a, T>,
+{
+    or_panic(try_read_records(path, parser))
+}
+
+pub fn try_read_records<T, P>(path: &Path, parser: P) -> Result<Vec<T>>
+where
+    P: for<'a> FnMut(&'a str) -> ParseResult<'a, T>,
+{
+    Input::try_read(path)?.try_records(parser)
+}
+
+// Same as 'Input::section_records', for records which don't borrow from the input
+pub fn read_section_records<T, P>(path: &Path, parser: P) -> Vec<T>
+where
+    P: for<'a> FnMut(&'a str) -> ParseResult<'a, T>,
+{
+    or_panic(try_read_section_records(path, parser))
+}
+
+pub fn try_read_section_records<T, P>(path: &Path, parser: P) -> Result<Vec<T>>
+where
+    P: for<'a> FnMut(&'a str) -> ParseResult<'a, T>,
+{
+    Input::try_read(path)?.try_section_records(parser)
+}
+
+// Fallible readers //
+
+pub fn try_read_file_line(path: &Path) -> Result<Vec<String>> {
+    let input = try_read_file_string(path)?;
+    Ok(input.lines().map(String::from).collect())
+}
+
+pub fn try_read_file_vec<T>(path: &Path) -> Result<Vec<T>>
+where
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    try_read_file_line(path)?
+        .into_iter()
+        .enumerate()
+        .map(|(i, s)| {
+            s.parse().map_err(|e| Error::Parse {
+                path: path.to_path_buf(),
+                line: i + 1,
+                text: s,
+                source: Box::new(e),
+            })
+        })
+        .collect()
+}
+
+pub fn try_read_file_grid<T>(path: &Path) -> Result<Grid<T>>
+where
+    T: From<char>,
+{
+    let vec: Vec<Vec<_>> = try_read_file_line(path)?
+        .into_iter()
         .map(|s| s.chars().map(T::from).collect())
         .collect();
-    Grid::from(vec)
+    Ok(Grid::from(vec))
 }
 
-// Returns strings (with newline), separated by blank line
-pub fn read_file_blankline(path: &Path) -> impl Iterator<Item = String> {
-    let mut input = String::new();
-    File::open(path)
-        .expect("File open error")
-        .read_to_string(&mut input)
-        .expect("File read error");
-    let lines_vec: Vec<_> = input.split("\n\n").map(|s| String::from(s)).collect();
-    lines_vec.into_iter()
+pub fn try_read_file_blankline(path: &Path) -> Result<Vec<String>> {
+    let sections = try_read_file_sections(path)?;
+    Ok(sections.into_iter().map(|lines| lines.join("\n")).collect())
+}
+
+// Sections are separated by one or more blank (or whitespace-only) lines.
+// Both LF and CRLF line endings are accepted, and leading or trailing blank lines are ignored.
+pub fn try_read_file_sections(path: &Path) -> Result<Vec<Vec<String>>> {
+    let input = try_read_file_string(path)?;
+    Ok(split_sections(&input)
+        .into_iter()
+        .map(|lines| lines.into_iter().map(String::from).collect())
+        .collect())
+}
+
+// Section which starts with a header line, e.g.
+//   your ticket:
+//   7,1,14
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    pub header: String, // Without trailing ':'
+    pub lines: Vec<String>,
+}
+
+pub fn try_read_file_headed_sections(path: &Path) -> Result<Vec<Section>> {
+    let sections = try_read_file_sections(path)?;
+    Ok(sections
+        .into_iter()
+        .map(|mut lines| {
+            let header = lines.remove(0);
+            Section {
+                header: String::from(header.trim_end().trim_end_matches(':')),
+                lines,
+            }
+        })
+        .collect())
+}
+
+pub fn split_sections(input: &str) -> Vec<Vec<&str>> {
+    let mut sections = vec![Vec::new()];
+    input.lines().for_each(|line| {
+        if line.trim().is_empty() {
+            if !sections.last().unwrap().is_empty() {
+                sections.push(Vec::new());
+            }
+        } else {
+            sections.last_mut().unwrap().push(line);
+        }
+    });
+    if sections.last().unwrap().is_empty() {
+        sections.pop();
+    }
+    sections
+}
+
+// Reads the copy compiled in with the 'embed-inputs' feature for "embedded:" paths
+// (see 'embedded::path'), and the file on disk otherwise.
+// Warns when the input does not match 'inputs/manifest.toml' (see 'manifest').
+fn try_read_file_string(path: &Path) -> Result<String> {
+    let (path, input) = match embedded::strip(path) {
+        Some(name) => (name, try_read_embedded(name)?),
+        None => (path, try_read_file_unchecked(path)?),
+    };
+    manifest::warn_on_mismatch(path, &input);
+    Ok(input)
+}
+
+// The compiled-in copy of 'path', either plain or encrypted
+fn try_read_embedded(path: &Path) -> Result<String> {
+    match (embedded::get(path), embedded::get_encrypted(path)) {
+        (Some(contents), _) => Ok(String::from(contents)),
+        (None, Some(data)) => crypto::decrypt_string(data, &crypto::encrypted_path(path)),
+        (None, None) => Err(Error::Io {
+            path: path.to_path_buf(),
+            source: std::io::Error::new(std::io::ErrorKind::NotFound, "not an embedded input"),
+        }),
+    }
+}
+
+// When 'path' doesn't exist, falls back to its encrypted copy 'path.enc' (see 'crypto')
+pub fn try_read_file_unchecked(path: &Path) -> Result<String> {
+    let io_error = |source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    match File::open(path) {
+        Ok(mut file) => {
+            let mut input = String::new();
+            file.read_to_string(&mut input).map_err(io_error)?;
+            Ok(input)
+        }
+        Err(_) if crypto::encrypted_path(path).exists() => crypto::read_encrypted(path),
+        Err(e) => Err(io_error(e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    // Parse error which is only 'Debug', as 'read_file_vec' accepted before 'try_read_file_vec'
+    #[derive(Debug, PartialEq)]
+    struct Even(u32);
+
+    impl FromStr for Even {
+        type Err = String;
+        fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+            match s.parse() {
+                Ok(n) if n % 2 == 0 => Ok(Even(n)),
+                _ => Err(format!("{} is not even", s)),
+            }
+        }
+    }
+
+    fn temp_file(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("aoc-io-{}-{}", std::process::id(), name));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn read_vec() {
+        let path = temp_file("even.txt", "2\n4\n10\n");
+        let values: Vec<Even> = read_file_vec(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(values, vec![Even(2), Even(4), Even(10)]);
+    }
+
+    #[test]
+    #[should_panic(expected = ":2: cannot parse \"3\": \"3 is not even\"")]
+    fn read_vec_reports_line() {
+        let path = temp_file("odd.txt", "2\n3\n");
+        let result = std::panic::catch_unwind(|| read_file_vec::<Even>(&path));
+        fs::remove_file(&path).unwrap();
+        std::panic::resume_unwind(result.unwrap_err());
+    }
 }
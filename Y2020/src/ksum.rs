@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+
+// Every distinct set of 'k' indices whose values add up to 'target'.
+// Each set is sorted, and the sets are sorted lexicographically.
+// O(n) for k = 1, O(n) hashing for k = 2 (plus the output), and
+// sort + two pointers under k - 2 fixed values for k >= 3, i.e. O(n^(k-1)).
+pub fn k_sum(values: &[i64], k: usize, target: i64) -> Vec<Vec<usize>> {
+    let mut found = match k {
+        0 if target == 0 => vec![vec![]],
+        0 => vec![],
+        1 => (0..values.len())
+            .filter(|&i| values[i] == target)
+            .map(|i| vec![i])
+            .collect(),
+        2 => two_sum_hashed(values, target),
+        _ => {
+            let mut order: Vec<usize> = (0..values.len()).collect();
+            order.sort_by_key(|&i| values[i]);
+            let mut found = vec![];
+            fix_and_search(values, &order, 0, k, target.into(), &mut vec![], &mut found);
+            found
+        }
+    };
+    found.iter_mut().for_each(|indices| indices.sort_unstable());
+    found.sort();
+    found
+}
+
+// Pairs (i, j), i < j, by looking up 'target - values[j]' among the values before j
+fn two_sum_hashed(values: &[i64], target: i64) -> Vec<Vec<usize>> {
+    let mut seen: HashMap<i64, Vec<usize>> = HashMap::new();
+    let mut found = vec![];
+    for (j, &value) in values.iter().enumerate() {
+        // No partner when 'target - value' is out of range
+        if let Some(partners) = target.checked_sub(value).and_then(|want| seen.get(&want)) {
+            found.extend(partners.iter().map(|&i| vec![i, j]));
+        }
+        seen.entry(value).or_default().push(j);
+    }
+    found
+}
+
+// Fixes values at increasing positions of 'order' (indices sorted by value)
+// until two remain, which are found by 'two_sum_sorted'.
+// What remains of the target is widened, since 'target - values[i]' can leave the range of i64.
+fn fix_and_search(
+    values: &[i64],
+    order: &[usize],
+    start: usize,
+    k: usize,
+    target: i128,
+    fixed: &mut Vec<usize>,
+    found: &mut Vec<Vec<usize>>,
+) {
+    if k == 2 {
+        let pairs = two_sum_sorted(values, &order[start..], target);
+        found.extend(pairs.into_iter().map(|(i, j)| {
+            let mut indices = fixed.clone();
+            indices.extend_from_slice(&[i, j]);
+            indices
+        }));
+        return;
+    }
+    if order.len() < start + k {
+        return;
+    }
+    for pos in start..=order.len() - k {
+        let i = order[pos];
+        fixed.push(i);
+        let rest = target - i128::from(values[i]);
+        fix_and_search(values, order, pos + 1, k - 1, rest, fixed, found);
+        fixed.pop();
+    }
+}
+
+// Two pointers over indices sorted by value. Runs of equal values are matched as a whole,
+// so that every pair of indices is found, not only every pair of values.
+fn two_sum_sorted(values: &[i64], order: &[usize], target: i128) -> Vec<(usize, usize)> {
+    let value = |pos: usize| i128::from(values[order[pos]]);
+    let mut found = vec![];
+    if order.len() < 2 {
+        return found;
+    }
+    let (mut lo, mut hi) = (0, order.len() - 1);
+    while lo < hi {
+        let sum = value(lo) + value(hi);
+        if sum < target {
+            lo += 1;
+        } else if sum > target {
+            hi -= 1;
+        } else if value(lo) == value(hi) {
+            // Everything in lo..=hi is the same value, so any two of them match
+            found.extend((lo..hi).flat_map(|a| (a + 1..=hi).map(move |b| (order[a], order[b]))));
+            break;
+        } else {
+            let lo_end = (lo..hi).find(|&p| value(p) != value(lo)).unwrap_or(hi);
+            let hi_start = (lo_end..=hi)
+                .rev()
+                .find(|&p| value(p) != value(hi))
+                .map_or(lo_end, |p| p + 1);
+            found.extend(
+                (lo..lo_end).flat_map(|a| (hi_start..=hi).map(move |b| (order[a], order[b]))),
+            );
+            lo = lo_end;
+            hi = hi_start - 1;
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+
+    // Every k-combination of indices, checked one by one
+    fn brute_force(values: &[i64], k: usize, target: i64) -> Vec<Vec<usize>> {
+        (0..values.len())
+            .combinations(k)
+            .filter(|indices| {
+                indices.iter().map(|&i| values[i] as i128).sum::<i128>() == target as i128
+            })
+            .collect()
+    }
+
+    #[test]
+    fn two_sum_sorted_equal_runs() {
+        let values = [1, 2, 2, 2, 3, 3, 4];
+        let mut order: Vec<usize> = (0..values.len()).collect();
+        order.sort_by_key(|&i| values[i]);
+        let mut pairs = two_sum_sorted(&values, &order, 5);
+        pairs.sort();
+        // 1 + 4, and each 2 with each 3
+        let expected = vec![(0, 6), (1, 4), (1, 5), (2, 4), (2, 5), (3, 4), (3, 5)];
+        assert_eq!(pairs, expected);
+        // A run matching itself gives every pair in it
+        let mut pairs = two_sum_sorted(&values, &order, 4);
+        pairs.sort();
+        assert_eq!(pairs, vec![(0, 4), (0, 5), (1, 2), (1, 3), (2, 3)]);
+        assert_eq!(two_sum_sorted(&[5], &[0], 10), vec![]);
+    }
+
+    #[test]
+    fn small_k() {
+        let values = [3, 1, 3, 2];
+        assert_eq!(k_sum(&values, 0, 0), vec![vec![]]);
+        assert_eq!(k_sum(&values, 0, 1), Vec::<Vec<usize>>::new());
+        assert_eq!(k_sum(&values, 1, 3), vec![vec![0], vec![2]]);
+        assert_eq!(k_sum(&values, 1, 4), Vec::<Vec<usize>>::new());
+    }
+
+    #[test]
+    fn k_greater_than_n() {
+        let values = [1, 1, 1];
+        assert_eq!(k_sum(&values, 4, 4), Vec::<Vec<usize>>::new());
+        assert_eq!(k_sum(&values, 3, 3), vec![vec![0, 1, 2]]);
+        assert_eq!(k_sum(&[], 2, 0), Vec::<Vec<usize>>::new());
+    }
+
+    #[test]
+    fn distinct_indices() {
+        // The same value is never used twice for one index
+        assert_eq!(k_sum(&[5, 1], 2, 10), Vec::<Vec<usize>>::new());
+        assert_eq!(k_sum(&[5, 5], 2, 10), vec![vec![0, 1]]);
+        let values = [1721, 979, 366, 299, 675, 1456, 0, 1010, 1010, -3, 1010];
+        for k in 2..=4 {
+            for &target in &[2020, 2017, 3030, 1010] {
+                assert_eq!(k_sum(&values, k, target), brute_force(&values, k, target));
+            }
+        }
+    }
+
+    #[test]
+    fn no_overflow() {
+        let values = [i64::MAX, i64::MIN, -1, 1, i64::MAX];
+        for k in 1..=4 {
+            for &target in &[i64::MAX, i64::MIN, 0, -1] {
+                assert_eq!(k_sum(&values, k, target), brute_force(&values, k, target));
+            }
+        }
+    }
+}
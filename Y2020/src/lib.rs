@@ -1,3 +1,19 @@
+// Lets '#[derive(AocParse)]', which names this crate by its path, be used inside it
+extern crate self as advent_of_code_rust_2020;
+
+pub mod automaton;
+pub mod bitgrid;
+pub mod crypto;
+pub mod embedded;
+pub mod error;
+pub mod expanding_grid;
+pub mod graph;
 pub mod grid;
+pub mod image;
 pub mod io;
+pub mod ksum;
+pub mod manifest;
 pub mod nom;
+pub mod parse;
+pub mod runner;
+pub mod validate;
@@ -0,0 +1,229 @@
+// 'inputs/manifest.toml': what each 'dayXX.txt' looked like when its answers were recorded,
+// so that a changed answer can be told apart from a replaced input.
+//
+//   [day01]
+//   sha256 = "..."
+//   lines = 200
+//   bytes = 1000
+//
+//   [day01.answers]   # Computed from the input with this hash
+//   sha256 = "..."
+//   part1 = "471019"
+//   part2 = "103927824"
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::{Error, Result};
+
+pub const FILE_NAME: &str = "manifest.toml";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    pub sha256: String,
+    pub lines: usize,
+    pub bytes: usize,
+    pub answers: Option<Answers>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Answers {
+    pub sha256: String, // Of the input the answers were computed from
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+// Entries by input name ('day01' for 'day01.txt')
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Manifest(pub BTreeMap<String, Entry>);
+
+// Fields of an input which differ from its manifest entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub name: String,
+    pub expected: Entry,
+    pub actual: Entry,
+}
+
+impl Entry {
+    pub fn of(text: &str) -> Self {
+        Entry {
+            sha256: sha256_hex(text),
+            lines: text.lines().count(),
+            bytes: text.len(),
+            answers: None,
+        }
+    }
+
+    fn same_input(&self, other: &Entry) -> bool {
+        (&self.sha256, self.lines, self.bytes) == (&other.sha256, other.lines, other.bytes)
+    }
+}
+
+impl Manifest {
+    // The manifest next to 'input', if there is one
+    pub fn path_for(input: &Path) -> PathBuf {
+        input
+            .parent()
+            .unwrap_or_else(|| Path::new(""))
+            .join(FILE_NAME)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).map_err(|source| Error::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        toml::from_str(&text).map_err(|e| Error::Manifest {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let text = toml::to_string(self).map_err(|e| Error::Manifest {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        })?;
+        fs::write(path, text).map_err(|source| Error::Io {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    // None if 'name' is not listed, or its entry matches 'text'
+    pub fn check(&self, name: &str, text: &str) -> Option<Mismatch> {
+        let expected = self.0.get(name)?;
+        let actual = Entry::of(text);
+        if expected.same_input(&actual) {
+            None
+        } else {
+            Some(Mismatch {
+                name: String::from(name),
+                expected: expected.clone(),
+                actual,
+            })
+        }
+    }
+
+    // Records the current state of an input, keeping answers only if they were
+    // computed from the same input
+    pub fn update(&mut self, name: &str, text: &str) {
+        let mut entry = Entry::of(text);
+        entry.answers = self
+            .0
+            .get(name)
+            .and_then(|old| old.answers.clone())
+            .filter(|answers| answers.sha256 == entry.sha256);
+        self.0.insert(String::from(name), entry);
+    }
+
+    // Answers recorded for exactly this input
+    pub fn answers(&self, name: &str, text: &str) -> Option<&Answers> {
+        let sha256 = sha256_hex(text);
+        self.0
+            .get(name)?
+            .answers
+            .as_ref()
+            .filter(|answers| answers.sha256 == sha256)
+    }
+
+    pub fn set_answers(
+        &mut self,
+        name: &str,
+        text: &str,
+        part1: Option<String>,
+        part2: Option<String>,
+    ) {
+        self.update(name, text);
+        let entry = self.0.get_mut(name).expect("Should be updated");
+        entry.answers = Some(Answers {
+            sha256: entry.sha256.clone(),
+            part1,
+            part2,
+        });
+    }
+
+    // Entries whose answers were computed from another input
+    pub fn stale_answers(&self) -> Vec<&str> {
+        self.0
+            .iter()
+            .filter(|(_, entry)| {
+                let answers = entry.answers.as_ref();
+                answers.is_some_and(|a| a.sha256 != entry.sha256)
+            })
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+}
+
+// Name of an input in the manifest
+pub fn input_name(input: &Path) -> String {
+    input
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+// Warns on stderr if 'input' does not match the manifest next to it.
+// Inputs without a manifest (or without an entry in it) are not checked.
+pub fn warn_on_mismatch(input: &Path, text: &str) {
+    let manifest_path = Manifest::path_for(input);
+    if !manifest_path.exists() {
+        return;
+    }
+    match Manifest::load(&manifest_path) {
+        Ok(manifest) => {
+            if let Some(mismatch) = manifest.check(&input_name(input), text) {
+                eprintln!(
+                    "warning: {} does not match {} ({})",
+                    input.display(),
+                    manifest_path.display(),
+                    mismatch
+                );
+            }
+        }
+        Err(e) => eprintln!("warning: {}", e),
+    }
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (expected, actual) = (&self.expected, &self.actual);
+        let mut diffs = Vec::new();
+        if expected.sha256 != actual.sha256 {
+            diffs.push(format!(
+                "sha256 {} != {}",
+                short(&actual.sha256),
+                short(&expected.sha256)
+            ));
+        }
+        if expected.lines != actual.lines {
+            diffs.push(format!("{} lines != {}", actual.lines, expected.lines));
+        }
+        if expected.bytes != actual.bytes {
+            diffs.push(format!("{} bytes != {}", actual.bytes, expected.bytes));
+        }
+        write!(f, "{}", diffs.join(", "))?;
+        if expected.answers.is_some() {
+            write!(f, "; recorded answers are for the old input")?;
+        }
+        Ok(())
+    }
+}
+
+pub fn sha256_hex(text: &str) -> String {
+    Sha256::digest(text.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn short(sha256: &str) -> &str {
+    &sha256[..sha256.len().min(12)]
+}
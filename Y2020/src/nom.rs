@@ -1,28 +1,335 @@
 extern crate nom;
-use nom::character::complete::{char, digit1};
-use nom::combinator::{map_res, opt, recognize};
-use nom::{error::ErrorKind, sequence, IResult};
+use nom::branch::alt;
+use nom::bytes::complete::{tag, take_till1, take_while1};
+use nom::character::complete::{alpha1, char, digit1, hex_digit1, multispace0, satisfy, space0};
+use nom::combinator::{map, map_res, opt, recognize};
+use nom::error::{context, ErrorKind, ParseError, VerboseError, VerboseErrorKind};
+use nom::multi::{separated_list0, separated_list1};
+use nom::{sequence, Parser};
 
 use std::str::FromStr;
 
-pub fn error(input: &str, error_kind: ErrorKind) -> nom::Err<nom::error::Error<&str>> {
-    nom::Err::Failure(nom::error::Error::new(input, error_kind))
+use crate::error::RecordError;
+
+// Parsers keep the stack of 'context's they failed in,
+// so that failures can be reported with what was expected where (see 'report')
+pub type Error<'a> = VerboseError<&'a str>;
+pub type ParseResult<'a, T> = nom::IResult<&'a str, T, Error<'a>>;
+
+pub fn error<'a>(input: &'a str, error_kind: ErrorKind) -> nom::Err<Error<'a>> {
+    nom::Err::Failure(Error::from_error_kind(input, error_kind))
 }
 
-pub fn unwrap_parsed<T>(result: IResult<&str, T>) -> T {
+pub fn unwrap_parsed<'a, T>(input: &'a str, result: ParseResult<'a, T>) -> T {
     match result {
         Ok(("", x)) => x,
-        Ok((s, _)) => panic!(format!("Non-exhaustive parsing: {}", s)),
-        Err(e) => panic!(format!("{}", e)),
+        Ok((rest, _)) => {
+            let offset = offset(input, rest).unwrap_or(0);
+            panic!(
+                "{}",
+                RecordError::at(input, offset, "unexpected trailing input")
+            )
+        }
+        Err(e) => panic!("{}", report(input, &e)),
+    }
+}
+
+// Line, column and caret of where parsing 'input' failed, e.g.
+//   line 3, column 5: expected number (Digit), in entry > policy
+//       1-x a: abcde
+//         ^
+pub fn report<'a>(input: &'a str, error: &nom::Err<Error<'a>>) -> RecordError {
+    match error {
+        nom::Err::Error(e) | nom::Err::Failure(e) => {
+            let (rest, message) = describe(e);
+            RecordError::at(input, offset(input, rest).unwrap_or(0), &message)
+        }
+        nom::Err::Incomplete(_) => RecordError::at(input, input.len(), "unexpected end of input"),
     }
 }
 
-pub fn number<T>(input: &str) -> IResult<&str, T>
+// Innermost failing position, and what was expected there.
+// The innermost context names the expected sub-parser, and the rest are where it was.
+pub fn describe<'a>(error: &Error<'a>) -> (&'a str, String) {
+    let (rest, expected) = error
+        .errors
+        .iter()
+        .find_map(|(rest, kind)| match kind {
+            VerboseErrorKind::Char(c) => Some((*rest, format!("'{}'", c))),
+            VerboseErrorKind::Nom(kind) => Some((*rest, format!("{:?}", kind))),
+            VerboseErrorKind::Context(_) => None,
+        })
+        .unwrap_or(("", String::from("input")));
+    let mut contexts = error.errors.iter().filter_map(|(_, kind)| match kind {
+        VerboseErrorKind::Context(name) => Some(*name),
+        _ => None,
+    });
+    let message = match contexts.next() {
+        Some(name) => format!("expected {} ({})", name, expected),
+        None => format!("expected {}", expected),
+    };
+    let outer: Vec<&str> = contexts.collect();
+    if outer.is_empty() {
+        (rest, message)
+    } else {
+        let path: Vec<&str> = outer.into_iter().rev().collect();
+        (rest, format!("{}, in {}", message, path.join(" > ")))
+    }
+}
+
+// Position of 'rest' in 'input', if it is a slice of it
+pub fn offset(input: &str, rest: &str) -> Option<usize> {
+    let (start, at) = (input.as_ptr() as usize, rest.as_ptr() as usize);
+    if start <= at && at <= start + input.len() {
+        Some(at - start)
+    } else {
+        None
+    }
+}
+
+// Combinators for common puzzle syntax //
+
+// Signed decimal, e.g. '-12'
+pub fn number<'a, T>(input: &'a str) -> ParseResult<'a, T>
 where
     T: FromStr,
 {
-    map_res(
-        recognize(sequence::tuple((opt(char('-')), digit1))),
-        FromStr::from_str,
+    context(
+        "number",
+        map_res(
+            recognize(sequence::tuple((opt(char('-')), digit1))),
+            FromStr::from_str,
+        ),
     )(input)
 }
+
+pub fn unsigned<'a, T>(input: &'a str) -> ParseResult<'a, T>
+where
+    T: FromStr,
+{
+    context("unsigned number", map_res(digit1, FromStr::from_str))(input)
+}
+
+// Hexadecimal digits without prefix, e.g. 'ff0a'
+pub fn hex(input: &str) -> ParseResult<'_, u64> {
+    context(
+        "hex number",
+        map_res(hex_digit1, |s| u64::from_str_radix(s, 16)),
+    )(input)
+}
+
+// Binary digits without prefix, e.g. '1011'
+pub fn binary(input: &str) -> ParseResult<'_, u64> {
+    context(
+        "binary number",
+        map_res(take_while1(|c| c == '0' || c == '1'), |s| {
+            u64::from_str_radix(s, 2)
+        }),
+    )(input)
+}
+
+// Single ASCII letter
+pub fn letter(input: &str) -> ParseResult<'_, char> {
+    context("letter", satisfy(|c| c.is_ascii_alphabetic()))(input)
+}
+
+// Run of non-whitespace characters
+pub fn token(input: &str) -> ParseResult<'_, &str> {
+    context("token", take_till1(char::is_whitespace))(input)
+}
+
+// Inclusive 'a-b', e.g. '1-3'
+pub fn range<'a, T>(input: &'a str) -> ParseResult<'a, (T, T)>
+where
+    T: FromStr,
+{
+    context("range", sequence::separated_pair(number, char('-'), number))(input)
+}
+
+// 'key:value' with any alphanumeric key
+pub fn key_value<'a, T, P>(value: P) -> impl FnMut(&'a str) -> ParseResult<'a, (&'a str, T)>
+where
+    P: Parser<&'a str, T, Error<'a>>,
+{
+    sequence::separated_pair(
+        context(
+            "key",
+            take_while1(|c: char| c.is_alphanumeric() || c == '_'),
+        ),
+        char(':'),
+        value,
+    )
+}
+
+// 'key:value' with the given key
+pub fn field<'a, T, P>(key: &'static str, value: P) -> impl FnMut(&'a str) -> ParseResult<'a, T>
+where
+    P: Parser<&'a str, T, Error<'a>>,
+{
+    context(
+        key,
+        sequence::preceded(sequence::pair(tag(key), char(':')), value),
+    )
+}
+
+// Space-separated words before the first of 'keywords', as one slice
+// (e.g. 'light red' from 'light red bags'). The keyword is left unparsed.
+pub fn words_until<'a>(
+    keywords: &'static [&'static str],
+) -> impl FnMut(&'a str) -> ParseResult<'a, &'a str> {
+    context("words", move |input: &'a str| {
+        let mut name_len = 0;
+        let mut i = input;
+        loop {
+            let (after_word, word) = alpha1(i)?;
+            if keywords.contains(&word) {
+                break;
+            }
+            name_len = input.len() - after_word.len();
+            i = space0(after_word)?.0;
+        }
+        if name_len == 0 {
+            return Err(nom::Err::Error(Error::from_error_kind(i, ErrorKind::Alpha)));
+        }
+        Ok((i, &input[..name_len]))
+    })
+}
+
+// Comma-separated items, some of which are 'placeholder', e.g. '7,13,x,59'
+pub fn sparse_list<'a, T, P>(
+    placeholder: char,
+    item: P,
+) -> impl FnMut(&'a str) -> ParseResult<'a, Vec<Option<T>>>
+where
+    P: Parser<&'a str, T, Error<'a>>,
+{
+    separated_list1(
+        char(','),
+        alt((map(char(placeholder), |_| None), map(item, Some))),
+    )
+}
+
+// 'parser' surrounded by any whitespace, including newlines
+pub fn ws<'a, T, P>(parser: P) -> impl FnMut(&'a str) -> ParseResult<'a, T>
+where
+    P: Parser<&'a str, T, Error<'a>>,
+{
+    sequence::delimited(multispace0, parser, multispace0)
+}
+
+// Items separated by 'separator', with any whitespace around both,
+// e.g. '1, 2,3 ,\n 4' with ','
+pub fn list<'a, T, P>(separator: char, item: P) -> impl FnMut(&'a str) -> ParseResult<'a, Vec<T>>
+where
+    P: Parser<&'a str, T, Error<'a>>,
+{
+    separated_list0(ws(char(separator)), ws(item))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers() {
+        assert_eq!(number::<i32>("-12 x"), Ok((" x", -12)));
+        assert_eq!(unsigned::<u8>("255,"), Ok((",", 255)));
+        assert!(unsigned::<u8>("-1").is_err());
+        assert!(unsigned::<u8>("256").is_err());
+        assert_eq!(hex("ff0a!"), Ok(("!", 0xff0a)));
+        assert_eq!(binary("10112"), Ok(("2", 0b1011)));
+        assert!(binary("2").is_err());
+    }
+
+    #[test]
+    fn letters_and_tokens() {
+        assert_eq!(letter("ab"), Ok(("b", 'a')));
+        assert!(letter("1").is_err());
+        assert_eq!(token("#a1b2 next"), Ok((" next", "#a1b2")));
+        assert!(token(" x").is_err());
+    }
+
+    #[test]
+    fn ranges() {
+        assert_eq!(range::<i32>("1-3 a"), Ok((" a", (1, 3))));
+        assert_eq!(range::<i32>("-5--2"), Ok(("", (-5, -2))));
+        assert!(range::<i32>("1-").is_err());
+    }
+
+    #[test]
+    fn key_values() {
+        assert_eq!(
+            key_value(token)("hgt:183cm x"),
+            Ok((" x", ("hgt", "183cm")))
+        );
+        assert_eq!(field("byr", number::<i32>)("byr:1937"), Ok(("", 1937)));
+        assert!(field("byr", number::<i32>)("iyr:1937").is_err());
+    }
+
+    #[test]
+    fn words() {
+        let mut bag = words_until(&["bag", "bags"]);
+        assert_eq!(
+            bag("light red bags contain"),
+            Ok(("bags contain", "light red"))
+        );
+        assert_eq!(bag("faded  blue bag."), Ok(("bag.", "faded  blue")));
+        assert!(bag("bags contain").is_err());
+        assert!(bag("light red").is_err());
+    }
+
+    #[test]
+    fn lists() {
+        let schedules = sparse_list('x', number::<i32>)("7,13,x,59\n");
+        assert_eq!(
+            schedules,
+            Ok(("\n", vec![Some(7), Some(13), None, Some(59)]))
+        );
+        assert_eq!(ws(letter)(" \n a \n"), Ok(("", 'a')));
+        assert_eq!(
+            list(',', number::<i32>)("1, 2,3 ,\n 4"),
+            Ok(("", vec![1, 2, 3, 4]))
+        );
+        assert_eq!(list(',', number::<i32>)(""), Ok(("", vec![])));
+    }
+
+    #[test]
+    fn reports() {
+        let input = "1-3 a\n1-x b";
+        let mut policy = context(
+            "policy",
+            sequence::separated_pair(number::<i32>, char('-'), number::<i32>),
+        );
+        let error = policy(&input[6..]).unwrap_err();
+        let report = report(input, &error);
+        assert_eq!((report.line, report.column), (2, 3));
+        assert_eq!(report.text, "1-x b");
+        assert_eq!(report.message, "expected number (Digit), in policy");
+        assert_eq!(
+            report.to_string(),
+            "line 2, column 3: expected number (Digit), in policy\n    1-x b\n      ^"
+        );
+    }
+
+    #[test]
+    fn reports_expected_char() {
+        let input = "1 3";
+        let mut policy = context(
+            "policy",
+            sequence::separated_pair(number::<i32>, char('-'), number::<i32>),
+        );
+        let report = report(input, &policy(input).unwrap_err());
+        assert_eq!((report.line, report.column), (1, 2));
+        assert_eq!(report.message, "expected policy ('-')");
+    }
+
+    #[test]
+    fn reports_nested_contexts() {
+        let input = "1-3 a\n1-x b";
+        let mut policy = context("policy", sequence::pair(range::<i32>, ws(letter)));
+        let error = policy(&input[6..]).unwrap_err();
+        let report = report(input, &error);
+        assert_eq!((report.line, report.column), (2, 3));
+        assert_eq!(report.message, "expected number (Digit), in policy > range");
+    }
+}
@@ -0,0 +1,160 @@
+// Support for '#[derive(AocParse)]', which generates a nom parser and 'FromStr'
+// from a format string:
+//
+//   #[derive(AocParse)]
+//   #[aoc(format = "{num1}-{num2} {letter}")]
+//   struct Policy { letter: char, num1: i32, num2: i32 }
+//
+// Each '{field}' spans the text up to the literal following it (or the end of the line),
+// and is parsed with the field type's 'AocParse' if it has one, and 'FromStr' otherwise.
+// '{{' and '}}' are literal braces.
+use std::any;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use nom::bytes::complete::{tag, take_till, take_until};
+use nom::combinator::all_consuming;
+use nom::error::{context, ContextError, ErrorKind, ParseError};
+
+pub use aoc_derive::AocParse;
+
+use crate::error::RecordError;
+use crate::nom::{report, Error, ParseResult};
+
+pub trait AocParse: Sized {
+    fn parse(input: &str) -> ParseResult<'_, Self>;
+}
+
+// 'FromStr' for derived types: the whole of 's' must match the format
+pub fn from_str<T: AocParse>(s: &str) -> Result<T, RecordError> {
+    all_consuming(T::parse)(s)
+        .map(|(_, value)| value)
+        .map_err(|e| report(s, &e))
+}
+
+pub fn literal<'a>(text: &'static str) -> impl FnMut(&'a str) -> ParseResult<'a, &'a str> {
+    tag(text)
+}
+
+// Cuts the text of field 'name' before 'terminator' (or at the end of the line),
+// and parses it with 'parse_segment'
+pub fn field<'a, T, P>(
+    name: &'static str,
+    terminator: Option<&'static str>,
+    parse_segment: P,
+) -> impl FnMut(&'a str) -> ParseResult<'a, T>
+where
+    P: Fn(&'a str) -> ParseResult<'a, T>,
+{
+    context(name, move |input: &'a str| {
+        let (input, segment) = match terminator {
+            Some(terminator) => take_until(terminator)(input)?,
+            None => take_till(|c| c == '\n' || c == '\r')(input)?,
+        };
+        let (_, value) = parse_segment(segment)?;
+        Ok((input, value))
+    })
+}
+
+// Chooses how a field type is parsed, by autoref-based specialization:
+// '(&&Field::<T>::new()).parse_segment(s)' resolves to 'ViaAocParse' when 'T: AocParse',
+// since it needs one less auto-deref than 'ViaFromStr'.
+pub struct Field<T>(PhantomData<T>);
+
+impl<T> Field<T> {
+    pub fn new() -> Self {
+        Field(PhantomData)
+    }
+}
+
+impl<T> Default for Field<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub trait ViaAocParse<T> {
+    fn parse_segment<'a>(&self, segment: &'a str) -> ParseResult<'a, T>;
+}
+
+impl<T: AocParse> ViaAocParse<T> for &Field<T> {
+    fn parse_segment<'a>(&self, segment: &'a str) -> ParseResult<'a, T> {
+        all_consuming(T::parse)(segment)
+    }
+}
+
+pub trait ViaFromStr<T> {
+    fn parse_segment<'a>(&self, segment: &'a str) -> ParseResult<'a, T>;
+}
+
+impl<T: FromStr> ViaFromStr<T> for Field<T> {
+    fn parse_segment<'a>(&self, segment: &'a str) -> ParseResult<'a, T> {
+        T::from_str(segment).map(|value| ("", value)).map_err(|_| {
+            let error = Error::from_error_kind(segment, ErrorKind::MapRes);
+            nom::Err::Error(Error::add_context(segment, any::type_name::<T>(), error))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::read_file_vec;
+    use std::fs;
+
+    #[derive(AocParse, Debug, PartialEq)]
+    #[aoc(format = "{num1}-{num2} {letter}")]
+    struct Policy {
+        letter: char,
+        num1: i32,
+        num2: i32,
+    }
+
+    #[derive(AocParse, Debug, PartialEq)]
+    #[aoc(format = "{policy}: {password}")]
+    struct Entry {
+        policy: Policy,
+        password: String,
+    }
+
+    #[derive(AocParse, Debug, PartialEq)]
+    #[aoc(format = "{{{name}}}")]
+    struct Braced {
+        name: String,
+    }
+
+    fn entry(num1: i32, num2: i32, letter: char, password: &str) -> Entry {
+        let policy = Policy { letter, num1, num2 };
+        let password = password.to_string();
+        Entry { policy, password }
+    }
+
+    #[test]
+    fn from_str() {
+        assert_eq!("1-3 a: abcde".parse(), Ok(entry(1, 3, 'a', "abcde")));
+        assert_eq!(
+            "{x}".parse(),
+            Ok(Braced {
+                name: "x".to_string()
+            })
+        );
+        assert!("1-3 a".parse::<Entry>().is_err());
+        assert!("1-3 a: abcde\n".parse::<Entry>().is_err());
+        assert!("x-3 a: abcde".parse::<Entry>().is_err());
+        assert!("1-3 ab: abcde".parse::<Entry>().is_err());
+    }
+
+    #[test]
+    fn read_file() {
+        let path = std::env::temp_dir().join(format!("aoc-parse-{}.txt", std::process::id()));
+        fs::write(&path, "1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n").unwrap();
+        let entries: Vec<Entry> = read_file_vec(&path);
+        fs::remove_file(&path).unwrap();
+        let expected = vec![
+            entry(1, 3, 'a', "abcde"),
+            entry(1, 3, 'b', "cdefg"),
+            entry(2, 9, 'c', "ccccccccc"),
+        ];
+        assert_eq!(entries, expected);
+    }
+}
@@ -0,0 +1,124 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::embedded;
+
+// Value following '--name' in the command line arguments
+pub fn arg_value(name: &str) -> Option<String> {
+    env::args().skip_while(|a| a != name).nth(1)
+}
+
+pub fn has_flag(name: &str) -> bool {
+    env::args().any(|a| a == name)
+}
+
+// '--input <path>' overrides the default input path. Otherwise, with 'embed-inputs',
+// the compiled-in copy of 'default' is read (see 'embedded::path'), so that files on disk
+// can't shadow it.
+pub fn input_path(default: &str) -> PathBuf {
+    match arg_value("--input") {
+        Some(path) => PathBuf::from(path),
+        None => embedded::path(Path::new(default)).unwrap_or_else(|| PathBuf::from(default)),
+    }
+}
+
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+// Timings go to stderr, so that stdout only has answers
+pub fn timed<T>(label: &str, f: impl FnOnce() -> T) -> T {
+    let (result, elapsed) = measure(f);
+    report_timing(label, elapsed);
+    result
+}
+
+pub fn report_timing(label: &str, elapsed: Duration) {
+    eprintln!("[{}] {:.3?}", label, elapsed);
+}
+
+// Columns padded to their widest cell, the first left-aligned and the rest right-aligned
+pub fn format_table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let header: Vec<String> = header.iter().map(|&h| String::from(h)).collect();
+    let all_rows: Vec<&Vec<String>> = std::iter::once(&header).chain(rows.iter()).collect();
+    let widths: Vec<usize> = (0..header.len())
+        .map(|j| {
+            let cell_len = |row: &&Vec<String>| row.get(j).map_or(0, |c| c.chars().count());
+            all_rows.iter().map(cell_len).max().unwrap_or(0)
+        })
+        .collect();
+    all_rows
+        .iter()
+        .map(|row| {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(widths.iter())
+                .enumerate()
+                .map(|(j, (cell, &width))| {
+                    if j == 0 {
+                        format!("{:<width$}", cell, width = width)
+                    } else {
+                        format!("{:>width$}", cell, width = width)
+                    }
+                })
+                .collect();
+            format!("{}\n", cells.join("  ").trim_end())
+        })
+        .collect()
+}
+
+// Comma-separated rows; cells with commas, quotes or line breaks are quoted (RFC 4180)
+pub fn format_csv(header: &[&str], rows: &[Vec<String>]) -> String {
+    let quote = |cell: &str| {
+        if cell.contains(&[',', '"', '\n', '\r'][..]) {
+            format!("\"{}\"", cell.replace('"', "\"\""))
+        } else {
+            String::from(cell)
+        }
+    };
+    let header: Vec<String> = header.iter().map(|&h| quote(h)).collect();
+    let rows = rows
+        .iter()
+        .map(|row| row.iter().map(|cell| quote(cell)).collect::<Vec<String>>());
+    std::iter::once(header)
+        .chain(rows)
+        .map(|cells| format!("{}\n", cells.join(",")))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(cells: &[&str]) -> Vec<String> {
+        cells.iter().map(|&cell| String::from(cell)).collect()
+    }
+
+    #[test]
+    fn csv_quoting() {
+        let rows = vec![
+            row(&["plain", "with space", ""]),
+            row(&["a,b", "say \"hi\"", "two\nlines"]),
+            row(&["cr\r", "crlf\r\n", "'single'"]),
+        ];
+        let csv = format_csv(&["name", "value, quoted", "note"], &rows);
+        let expected = concat!(
+            "name,\"value, quoted\",note\n",
+            "plain,with space,\n",
+            "\"a,b\",\"say \"\"hi\"\"\",\"two\nlines\"\n",
+            "\"cr\r\",\"crlf\r\n\",'single'\n",
+        );
+        assert_eq!(csv, expected);
+        assert_eq!(format_csv(&["only"], &[]), "only\n");
+    }
+
+    #[test]
+    fn table() {
+        let rows = vec![row(&["byr", "1"]), row(&["hgt", "12"])];
+        let table = format_table(&["field", "n"], &rows);
+        assert_eq!(table, "field   n\nbyr     1\nhgt    12\n");
+    }
+}
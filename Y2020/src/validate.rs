@@ -0,0 +1,352 @@
+// Declarative validation of field-based records (e.g. passports):
+//
+//   const SCHEMA: Schema = Schema(&[
+//       Field::required("byr", &[Rule::YearRange(1920, 2002)]),
+//       Field::optional("cid", &[]),
+//   ]);
+//   let violations = SCHEMA.validate(&record);
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    // Four digits, within the inclusive range
+    YearRange(u32, u32),
+    // Digits followed by one of the units, within the unit's inclusive range
+    NumberWithUnit(&'static [(&'static str, u32, u32)]),
+    // '#' followed by six lowercase hex digits
+    HexColor,
+    OneOf(&'static [&'static str]),
+    // Exactly this many digits
+    Digits(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Field {
+    pub name: &'static str,
+    pub required: bool,
+    pub rules: &'static [Rule],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Schema(pub &'static [Field]);
+
+// Field values by name
+pub trait Record {
+    fn field(&self, name: &str) -> Option<&str>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub field: &'static str,
+    pub kind: ViolationKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ViolationKind {
+    Missing,
+    Invalid { value: String, reason: String },
+}
+
+// Keys which don't make a record invalid, but may be mistakes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    Unknown(String),
+    Duplicate(String),
+}
+
+impl Rule {
+    // Why 'value' breaks the rule, if it does
+    pub fn check(&self, value: &str) -> Result<(), String> {
+        match *self {
+            Rule::YearRange(min, max) => {
+                let year = digits(value, Some(4)).ok_or("expected a four-digit year")?;
+                within(year, min, max, "")
+            }
+            Rule::NumberWithUnit(units) => {
+                let split = value
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(value.len());
+                let (number, unit) = value.split_at(split);
+                let expected = || {
+                    let names: Vec<&str> = units.iter().map(|&(unit, _, _)| unit).collect();
+                    format!("expected a number followed by {}", names.join(" or "))
+                };
+                let number = digits(number, None).ok_or_else(expected)?;
+                let &(unit, min, max) = units
+                    .iter()
+                    .find(|&&(u, _, _)| u == unit)
+                    .ok_or_else(expected)?;
+                within(number, min, max, unit)
+            }
+            Rule::HexColor => {
+                let is_hex = |c: char| c.is_ascii_digit() || ('a'..='f').contains(&c);
+                match value.strip_prefix('#') {
+                    Some(hex) if hex.len() == 6 && hex.chars().all(is_hex) => Ok(()),
+                    _ => Err(String::from(
+                        "expected '#' followed by six hex digits (0-9, a-f)",
+                    )),
+                }
+            }
+            Rule::OneOf(values) => {
+                if values.contains(&value) {
+                    Ok(())
+                } else {
+                    Err(format!("expected one of {}", values.join(", ")))
+                }
+            }
+            Rule::Digits(n) => {
+                if value.len() == n && value.chars().all(|c| c.is_ascii_digit()) {
+                    Ok(())
+                } else {
+                    Err(format!("expected {} digits", n))
+                }
+            }
+        }
+    }
+}
+
+impl Field {
+    pub const fn required(name: &'static str, rules: &'static [Rule]) -> Self {
+        Field {
+            name,
+            required: true,
+            rules,
+        }
+    }
+
+    pub const fn optional(name: &'static str, rules: &'static [Rule]) -> Self {
+        Field {
+            name,
+            required: false,
+            rules,
+        }
+    }
+
+    // Every broken rule of a present field, or 'Missing'
+    pub fn validate<R: Record>(&self, record: &R) -> Vec<Violation> {
+        let violation = |kind| Violation {
+            field: self.name,
+            kind,
+        };
+        match record.field(self.name) {
+            None if self.required => vec![violation(ViolationKind::Missing)],
+            None => vec![],
+            Some(value) => self
+                .rules
+                .iter()
+                .filter_map(|rule| rule.check(value).err())
+                .map(|reason| {
+                    violation(ViolationKind::Invalid {
+                        value: String::from(value),
+                        reason,
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Schema {
+    // Violations in the order of the schema's fields; empty if 'record' is valid
+    pub fn validate<R: Record>(&self, record: &R) -> Vec<Violation> {
+        self.0
+            .iter()
+            .flat_map(|field| field.validate(record))
+            .collect()
+    }
+
+    pub fn is_valid<R: Record>(&self, record: &R) -> bool {
+        self.validate(record).is_empty()
+    }
+
+    pub fn field(&self, name: &str) -> Option<&Field> {
+        self.0.iter().find(|field| field.name == name)
+    }
+
+    // Map of 'key:value' pairs, keeping the first value of a repeated key.
+    // Keys outside the schema are kept, with a warning.
+    pub fn collect<'a>(
+        &self,
+        pairs: &[(&'a str, &'a str)],
+    ) -> (HashMap<&'a str, &'a str>, Vec<Warning>) {
+        let init = (HashMap::new(), Vec::new());
+        pairs
+            .iter()
+            .fold(init, |(mut map, mut warnings), &(key, value)| {
+                if self.field(key).is_none() {
+                    warnings.push(Warning::Unknown(String::from(key)));
+                }
+                if map.contains_key(key) {
+                    warnings.push(Warning::Duplicate(String::from(key)));
+                } else {
+                    map.insert(key, value);
+                }
+                (map, warnings)
+            })
+    }
+}
+
+impl<K, V> Record for HashMap<K, V>
+where
+    K: std::borrow::Borrow<str> + std::hash::Hash + Eq,
+    V: AsRef<str>,
+{
+    fn field(&self, name: &str) -> Option<&str> {
+        self.get(name).map(|v| v.as_ref())
+    }
+}
+
+impl<K, V> Record for BTreeMap<K, V>
+where
+    K: std::borrow::Borrow<str> + Ord,
+    V: AsRef<str>,
+{
+    fn field(&self, name: &str) -> Option<&str> {
+        self.get(name).map(|v| v.as_ref())
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            ViolationKind::Missing => write!(f, "{}: missing", self.field),
+            ViolationKind::Invalid { value, reason } => {
+                write!(f, "{}: invalid value {:?} ({})", self.field, value, reason)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Warning::Unknown(key) => write!(f, "unknown key {:?}", key),
+            Warning::Duplicate(key) => write!(f, "duplicate key {:?}, keeping the first", key),
+        }
+    }
+}
+
+// Value of a string of ASCII digits, optionally of exactly 'len' digits
+fn digits(s: &str, len: Option<usize>) -> Option<u32> {
+    let len_ok = len.map_or(!s.is_empty(), |len| s.len() == len);
+    if len_ok && s.chars().all(|c| c.is_ascii_digit()) {
+        s.parse().ok()
+    } else {
+        None
+    }
+}
+
+fn within(n: u32, min: u32, max: u32, unit: &str) -> Result<(), String> {
+    if min <= n && n <= max {
+        Ok(())
+    } else {
+        Err(format!(
+            "{}{} is not within {}-{}{}",
+            n, unit, min, max, unit
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEIGHT: Rule = Rule::NumberWithUnit(&[("cm", 150, 193), ("in", 59, 76)]);
+
+    fn valid(rule: Rule, value: &str) -> bool {
+        rule.check(value).is_ok()
+    }
+
+    #[test]
+    fn year_range() {
+        let rule = Rule::YearRange(1920, 2002);
+        assert!(valid(rule, "1920") && valid(rule, "2002"));
+        assert!(!valid(rule, "1919") && !valid(rule, "2003"));
+        // Four digits, nothing else
+        assert!(!valid(rule, "02002"));
+        assert!(!valid(rule, "199"));
+        assert!(!valid(rule, "+1990"));
+        assert!(!valid(rule, "1990 "));
+        assert_eq!(
+            rule.check("2003"),
+            Err(String::from("2003 is not within 1920-2002"))
+        );
+    }
+
+    #[test]
+    fn number_with_unit() {
+        assert!(valid(HEIGHT, "150cm") && valid(HEIGHT, "193cm"));
+        assert!(valid(HEIGHT, "59in") && valid(HEIGHT, "76in"));
+        assert!(!valid(HEIGHT, "149cm") && !valid(HEIGHT, "77in"));
+        // Without a unit, with another one, or without a number
+        assert!(!valid(HEIGHT, "190"));
+        assert!(!valid(HEIGHT, "190mm"));
+        assert!(!valid(HEIGHT, "cm"));
+        assert!(!valid(HEIGHT, "190 cm"));
+        assert!(!valid(HEIGHT, "99999999999cm"));
+        assert_eq!(
+            HEIGHT.check("190"),
+            Err(String::from("expected a number followed by cm or in"))
+        );
+        assert_eq!(
+            HEIGHT.check("77in"),
+            Err(String::from("77in is not within 59-76in"))
+        );
+    }
+
+    #[test]
+    fn hex_color() {
+        assert!(valid(Rule::HexColor, "#123abc"));
+        assert!(!valid(Rule::HexColor, "#123ABC"));
+        assert!(!valid(Rule::HexColor, "#123abz"));
+        assert!(!valid(Rule::HexColor, "#123ab"));
+        assert!(!valid(Rule::HexColor, "#123abcd"));
+        assert!(!valid(Rule::HexColor, "123abc"));
+    }
+
+    #[test]
+    fn one_of_and_digits() {
+        let colors = Rule::OneOf(&["amb", "blu"]);
+        assert!(valid(colors, "blu"));
+        assert!(!valid(colors, "wat") && !valid(colors, "bl") && !valid(colors, "blue"));
+        let pid = Rule::Digits(9);
+        assert!(valid(pid, "000000001"));
+        assert!(!valid(pid, "0123456789"));
+        assert!(!valid(pid, "12345678"));
+        assert!(!valid(pid, "12345678a"));
+        assert_eq!(
+            pid.check("0123456789"),
+            Err(String::from("expected 9 digits"))
+        );
+    }
+
+    #[test]
+    fn schema() {
+        const SCHEMA: Schema = Schema(&[
+            Field::required("byr", &[Rule::YearRange(1920, 2002)]),
+            Field::required("hgt", &[HEIGHT]),
+            Field::optional("cid", &[]),
+        ]);
+        let pairs = [("hgt", "190"), ("cid", "1"), ("xyz", "2"), ("hgt", "60in")];
+        let (record, warnings) = SCHEMA.collect(&pairs);
+        assert_eq!(record.get("hgt"), Some(&"190"));
+        assert_eq!(
+            warnings,
+            vec![
+                Warning::Unknown(String::from("xyz")),
+                Warning::Duplicate(String::from("hgt"))
+            ]
+        );
+        let violations = SCHEMA.validate(&record);
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0].to_string(), "byr: missing");
+        assert_eq!(
+            violations[1].to_string(),
+            "hgt: invalid value \"190\" (expected a number followed by cm or in)"
+        );
+        let record: HashMap<&str, &str> =
+            [("byr", "2002"), ("hgt", "60in")].iter().copied().collect();
+        assert!(SCHEMA.is_valid(&record));
+    }
+}